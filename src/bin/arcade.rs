@@ -0,0 +1,149 @@
+//! Plays the day 13 arcade game in the terminal.
+//!
+//! Usage: `cargo run --features terminal --bin arcade [program file]`, where the
+//! program defaults to the puzzle input.
+
+use advent_of_code_2019::day13::{
+    get_program, track_ball, Arcade, ArcadeSession, ArcadeState, Joystick, Tile,
+};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::time::Instant;
+
+const CONTROLS: &str =
+    "<-/-> or a/d steer   space pause   r rewind   . step   +/- speed   t autopilot   q quit";
+
+/// Puts the terminal back how it was, however the game ends.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+enum Command {
+    Steer(Joystick),
+    TogglePause,
+    Rewind,
+    Step,
+    SpeedUp,
+    SlowDown,
+    ToggleAutopilot,
+    Quit,
+}
+
+fn command_for(key: KeyCode) -> Option<Command> {
+    match key {
+        KeyCode::Left | KeyCode::Char('a') => Some(Command::Steer(Joystick::Left)),
+        KeyCode::Right | KeyCode::Char('d') => Some(Command::Steer(Joystick::Right)),
+        KeyCode::Char(' ') | KeyCode::Char('p') => Some(Command::TogglePause),
+        KeyCode::Backspace | KeyCode::Char('r') => Some(Command::Rewind),
+        KeyCode::Char('.') => Some(Command::Step),
+        KeyCode::Char('+') | KeyCode::Char('=') => Some(Command::SpeedUp),
+        KeyCode::Char('-') => Some(Command::SlowDown),
+        KeyCode::Char('t') => Some(Command::ToggleAutopilot),
+        KeyCode::Esc | KeyCode::Char('q') => Some(Command::Quit),
+        _ => None,
+    }
+}
+
+fn draw(session: &ArcadeSession, autopilot: bool) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    queue!(stdout, cursor::MoveTo(0, 0))?;
+    for line in session.arcade().render().lines() {
+        write!(stdout, "{}\r\n", line)?;
+    }
+
+    let status = match (session.state(), session.is_paused()) {
+        (ArcadeState::GameOver, _) => "GAME OVER",
+        (_, true) => "PAUSED",
+        (_, false) => "",
+    };
+    write!(
+        stdout,
+        "Frame {}   Blocks left {}   {}ms per frame{}   {}",
+        session.frame(),
+        session.arcade().count_tiles(Tile::Block),
+        session.frame_delay().as_millis(),
+        if autopilot { "   autopilot" } else { "" },
+        status
+    )?;
+    queue!(stdout, terminal::Clear(ClearType::UntilNewLine))?;
+    write!(stdout, "\r\n{}\r\n", CONTROLS)?;
+
+    stdout.flush()
+}
+
+fn main() -> io::Result<()> {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input/2019/day13.txt".to_string());
+    let program = get_program(fs::read_to_string(path)?.trim())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    let mut session = ArcadeSession::new(Arcade::free_play(&program));
+    let mut autopilot = false;
+    let _terminal = RawTerminal::enter()?;
+    execute!(io::stdout(), terminal::Clear(ClearType::All))?;
+
+    loop {
+        draw(&session, autopilot)?;
+
+        // keys pressed before the frame is due decide where the joystick is held
+        let deadline = Instant::now() + session.frame_delay();
+        let mut joystick = Joystick::Neutral;
+        let mut step = false;
+        loop {
+            let now = Instant::now();
+            if now >= deadline || !event::poll(deadline - now)? {
+                break;
+            }
+            let code = match event::read()? {
+                Event::Key(KeyEvent {
+                    code,
+                    kind: KeyEventKind::Press,
+                    ..
+                }) => code,
+                _ => continue,
+            };
+            match command_for(code) {
+                Some(Command::Steer(direction)) => joystick = direction,
+                Some(Command::TogglePause) => session.toggle_pause(),
+                Some(Command::Rewind) => {
+                    if !session.is_paused() {
+                        session.toggle_pause();
+                    }
+                    session.rewind(1);
+                    draw(&session, autopilot)?;
+                }
+                Some(Command::Step) => step = true,
+                Some(Command::SpeedUp) => session.speed_up(),
+                Some(Command::SlowDown) => session.slow_down(),
+                Some(Command::ToggleAutopilot) => autopilot = !autopilot,
+                Some(Command::Quit) => return Ok(()),
+                None => {}
+            }
+        }
+
+        if !session.is_paused() || step {
+            if autopilot {
+                joystick = track_ball(session.arcade());
+            }
+            session.advance(joystick);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Saves the map explored by the day 15 repair droid, and analyses saved maps without
+//! running the droid again.
+//!
+//! Usage:
+//! - `cargo run --bin droid_map explore [program file] [map file]` explores the ship and
+//!   writes the map, to stdout if no map file is given. The program defaults to the
+//!   puzzle input.
+//! - `cargo run --bin droid_map analyse <map file>` draws the shortest path to the oxygen
+//!   system and reports how long the ship takes to fill with oxygen.
+
+use advent_of_code_2019::day15::{explore_ship, ShipMap};
+use std::env;
+use std::fs;
+use std::io;
+
+const USAGE: &str =
+    "usage: droid_map explore [program file] [map file]\n       droid_map analyse <map file>";
+
+fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn explore(program_path: Option<String>, map_path: Option<String>) -> io::Result<()> {
+    let program_path = program_path.unwrap_or_else(|| "input/2019/day15.txt".to_string());
+    let map = explore_ship(fs::read_to_string(program_path)?.trim()).map_err(invalid_data)?;
+    match map_path {
+        Some(path) => fs::write(path, map.to_text()),
+        None => {
+            print!("{}", map.to_text());
+            Ok(())
+        }
+    }
+}
+
+fn analyse(map_path: &str) -> io::Result<()> {
+    let map = ShipMap::parse(&fs::read_to_string(map_path)?).map_err(invalid_data)?;
+    let oxygen_system = match map.oxygen_system() {
+        Some(point) => point,
+        None => {
+            print!("{}", map.to_text());
+            println!("The droid never found the oxygen system");
+            return Ok(());
+        }
+    };
+
+    match map.shortest_path(map.start(), oxygen_system) {
+        Some(path) => {
+            print!("{}", map.render_path(&path));
+            println!("Oxygen system is {} steps away", path.len() - 1);
+        }
+        None => {
+            print!("{}", map.to_text());
+            println!("The oxygen system can't be reached from the start");
+        }
+    }
+    if let Some(minutes) = map.minutes_to_fill() {
+        println!("Oxygen fills the ship in {} minutes", minutes);
+    }
+
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let mut args = env::args().skip(1);
+    match (args.next().as_deref(), args.next()) {
+        (Some("explore"), program_path) => explore(program_path, args.next()),
+        (Some("analyse"), Some(map_path)) => analyse(&map_path),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, USAGE)),
+    }
+}
@@ -1,41 +1,249 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::num::ParseFloatError;
+use itertools::Itertools;
+use std::fmt::Write;
+use std::iter;
+use std::num::ParseIntError;
 
 #[aoc_generator(day1)]
-pub fn get_masses(input: &str) -> Result<Vec<f32>, ParseFloatError> {
-    input.lines().map(|l| l.parse::<f32>()).collect()
+pub fn get_masses(input: &str) -> Result<Vec<u64>, ParseIntError> {
+    input.lines().map(|l| l.parse::<u64>()).collect()
 }
 
 #[aoc(day1, part1)]
-pub fn part1(masses: &[f32]) -> f32 {
-    masses
-        .iter()
-        .fold(0.0, |acc, mass| acc + (mass / 3.0).floor() - 2.0)
+pub fn part1(masses: &[u64]) -> u64 {
+    masses.iter().map(|&mass| fuel_for_mass(mass)).sum()
 }
 
 #[aoc(day1, part2)]
-pub fn part2(masses: &[f32]) -> f32 {
-    masses
+pub fn part2(masses: &[u64]) -> u64 {
+    masses.iter().map(|&mass| calculate_fuel(mass)).sum()
+}
+
+/// Fuel needed to lift `mass`, ignoring the mass of the fuel itself. Anything light
+/// enough to need negative fuel needs none.
+pub fn fuel_for_mass(mass: u64) -> u64 {
+    (mass / 3).saturating_sub(2)
+}
+
+/// The fuel for `mass`, then the fuel for that fuel, and so on while any more is needed.
+pub fn fuel_chain(mass: u64) -> impl Iterator<Item = u64> {
+    iter::successors(Some(fuel_for_mass(mass)), |&fuel| Some(fuel_for_mass(fuel)))
+        .take_while(|&fuel| fuel > 0)
+}
+
+fn calculate_fuel(mass: u64) -> u64 {
+    fuel_chain(mass).sum()
+}
+
+/// How the fuel for one module adds up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleFuel {
+    pub mass: u64,
+    /// The module's own fuel first, followed by each round of fuel for fuel.
+    pub chain: Vec<u64>,
+}
+
+impl ModuleFuel {
+    pub fn new(mass: u64) -> Self {
+        ModuleFuel {
+            mass,
+            chain: fuel_chain(mass).collect(),
+        }
+    }
+
+    pub fn base_fuel(&self) -> u64 {
+        self.chain.first().copied().unwrap_or(0)
+    }
+
+    pub fn fuel_for_fuel(&self) -> &[u64] {
+        self.chain.get(1..).unwrap_or(&[])
+    }
+
+    pub fn total_fuel(&self) -> u64 {
+        self.chain.iter().sum()
+    }
+}
+
+pub fn fuel_breakdown(masses: &[u64]) -> Vec<ModuleFuel> {
+    masses.iter().map(|&mass| ModuleFuel::new(mass)).collect()
+}
+
+/// The breakdown as an aligned plain text table, with a row of totals at the bottom.
+pub fn fuel_table(breakdown: &[ModuleFuel]) -> String {
+    let header = ["module", "mass", "base fuel", "fuel for fuel", "total"];
+    let mut rows: Vec<[String; 5]> = breakdown
         .iter()
-        .fold(0.0, |acc, mass| acc + calculate_fuel(mass, 0.0))
+        .enumerate()
+        .map(|(index, module)| {
+            [
+                (index + 1).to_string(),
+                module.mass.to_string(),
+                module.base_fuel().to_string(),
+                describe_fuel_for_fuel(module.fuel_for_fuel()),
+                module.total_fuel().to_string(),
+            ]
+        })
+        .collect();
+    rows.push([
+        "total".to_string(),
+        breakdown
+            .iter()
+            .map(|module| module.mass)
+            .sum::<u64>()
+            .to_string(),
+        breakdown
+            .iter()
+            .map(ModuleFuel::base_fuel)
+            .sum::<u64>()
+            .to_string(),
+        breakdown
+            .iter()
+            .map(|module| module.fuel_for_fuel().iter().sum::<u64>())
+            .sum::<u64>()
+            .to_string(),
+        breakdown
+            .iter()
+            .map(ModuleFuel::total_fuel)
+            .sum::<u64>()
+            .to_string(),
+    ]);
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].len())
+                .chain(iter::once(header[column].len()))
+                .max()
+                .unwrap()
+        })
+        .collect();
+
+    let mut table = String::new();
+    write_table_row(&mut table, &header, &widths);
+    writeln!(
+        table,
+        "{}",
+        widths.iter().map(|&width| "-".repeat(width)).join("-+-")
+    )
+    .unwrap();
+    for row in rows.iter() {
+        write_table_row(&mut table, row, &widths);
+    }
+
+    table
+}
+
+fn describe_fuel_for_fuel(contributions: &[u64]) -> String {
+    match contributions {
+        [] => "0".to_string(),
+        [only] => only.to_string(),
+        _ => format!(
+            "{} = {}",
+            contributions.iter().join(" + "),
+            contributions.iter().sum::<u64>()
+        ),
+    }
 }
 
-fn calculate_fuel(mass: &f32, current_fuel_required: f32) -> f32 {
-    let required_fuel = (mass / 3.0).floor() - 2.0;
+/// Plain numbers are right aligned; the module label and the fuel for fuel sums are
+/// left aligned.
+fn write_table_row<S: AsRef<str>>(table: &mut String, cells: &[S], widths: &[usize]) {
+    let line = cells
+        .iter()
+        .zip(widths.iter())
+        .enumerate()
+        .map(|(column, (cell, &width))| match column {
+            1 | 2 | 4 => format!("{:>width$}", cell.as_ref(), width = width),
+            _ => format!("{:<width$}", cell.as_ref(), width = width),
+        })
+        .join(" | ");
+    writeln!(table, "{}", line.trim_end()).unwrap();
+}
 
-    if required_fuel <= 0.0 {
-        return current_fuel_required;
+/// The breakdown as CSV, one module per line. The fuel for fuel contributions are
+/// listed in order, separated by semicolons.
+pub fn fuel_csv(breakdown: &[ModuleFuel]) -> String {
+    let mut csv = String::from("module,mass,base_fuel,fuel_for_fuel,total_fuel,contributions\n");
+    for (index, module) in breakdown.iter().enumerate() {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{}",
+            index + 1,
+            module.mass,
+            module.base_fuel(),
+            module.fuel_for_fuel().iter().sum::<u64>(),
+            module.total_fuel(),
+            module.fuel_for_fuel().iter().join(";")
+        )
+        .unwrap();
     }
 
-    calculate_fuel(&required_fuel, current_fuel_required + required_fuel)
+    csv
 }
 
 #[cfg(test)]
 mod tests {
-    use super::calculate_fuel;
+    use super::*;
+    use indoc::indoc;
 
     #[test]
     fn test() {
-        assert_eq!(calculate_fuel(&100756.0, 0.0), 50346.0)
+        assert_eq!(calculate_fuel(100756), 50346)
+    }
+
+    #[test]
+    fn base_fuel() {
+        assert_eq!(part1(&[12, 14, 1969, 100756]), 2 + 2 + 654 + 33583);
+        assert_eq!(fuel_for_mass(5), 0);
+    }
+
+    #[test]
+    fn masses_too_big_for_floats() {
+        let mass = (1 << 40) + 1;
+        assert_eq!(fuel_for_mass(mass), 366503875923);
+        assert_eq!(part1(&[mass, mass]), 733007751846);
+    }
+
+    #[test]
+    fn breakdown() {
+        let module = ModuleFuel::new(1969);
+        assert_eq!(module.chain, vec![654, 216, 70, 21, 5]);
+        assert_eq!(module.base_fuel(), 654);
+        assert_eq!(module.fuel_for_fuel(), &[216, 70, 21, 5]);
+        assert_eq!(module.total_fuel(), 966);
+
+        let tiny = ModuleFuel::new(2);
+        assert_eq!((tiny.base_fuel(), tiny.total_fuel()), (0, 0));
+    }
+
+    #[test]
+    fn table() {
+        let breakdown = fuel_breakdown(&[14, 1969, 100756]);
+        assert_eq!(
+            fuel_table(&breakdown),
+            indoc!(
+                "module |   mass | base fuel | fuel for fuel                                         | total
+                -------+--------+-----------+-------------------------------------------------------+------
+                1      |     14 |         2 | 0                                                     |     2
+                2      |   1969 |       654 | 216 + 70 + 21 + 5 = 312                               |   966
+                3      | 100756 |     33583 | 11192 + 3728 + 1240 + 411 + 135 + 43 + 12 + 2 = 16763 | 50346
+                total  | 102739 |     34239 | 17075                                                 | 51314
+                "
+            )
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let breakdown = fuel_breakdown(&[14, 1969]);
+        assert_eq!(
+            fuel_csv(&breakdown),
+            indoc!(
+                "module,mass,base_fuel,fuel_for_fuel,total_fuel,contributions
+                1,14,2,0,2,
+                2,1969,654,312,966,216;70;21;5
+                "
+            )
+        );
     }
 }
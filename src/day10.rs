@@ -1,7 +1,7 @@
-use crate::grid::{Point, Vector};
+use crate::grid::{gcd, Grid, Point, Vector};
 use aoc_runner_derive::{aoc, aoc_generator};
 use ordered_float::OrderedFloat;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 #[aoc_generator(day10)]
 pub fn get_occupied_points(input: &str) -> HashSet<Point> {
@@ -30,24 +30,114 @@ pub fn part1(asteroids: &HashSet<Point>) -> usize {
 }
 
 fn find_max_station(asteroids: &HashSet<Point>) -> (Point, usize) {
-    let mut max_point = Point::origin();
-    let mut max_detectable = std::usize::MIN;
+    // with no asteroids there's nowhere to build, so nothing can be seen from the origin
+    AsteroidField::new(asteroids)
+        .ranked_stations()
+        .first()
+        .copied()
+        .unwrap_or((Point::origin(), 0))
+}
 
-    for curr in asteroids.iter() {
-        let unique_angles = get_vectors(&asteroids, &curr)
-            .iter()
-            .map(|vector| vector.angle_deg)
-            .collect::<HashSet<OrderedFloat<f64>>>();
+/// Answers line of sight questions by walking rays across a dense map rather than
+/// comparing angles between every pair of asteroids.
+pub struct AsteroidField {
+    occupied: Grid<bool>,
+    asteroids: Vec<Point>,
+    // every whole-number step that isn't a multiple of a smaller one
+    ray_steps: Vec<Point>,
+}
+
+impl AsteroidField {
+    /// Asteroid coordinates must be non-negative, as produced by `get_occupied_points`.
+    pub fn new(asteroids: &HashSet<Point>) -> Self {
+        let width = asteroids.iter().map(|point| point.x + 1).max().unwrap_or(0);
+        let height = asteroids.iter().map(|point| point.y + 1).max().unwrap_or(0);
 
-        let num_detectable = unique_angles.len();
+        let mut occupied = Grid::new(width as usize, height as usize, false);
+        for asteroid in asteroids {
+            *occupied.get_mut(asteroid).unwrap() = true;
+        }
+
+        let mut ray_steps = Vec::new();
+        for y in -(height - 1)..height {
+            for x in -(width - 1)..width {
+                if gcd(x.abs(), y.abs()) == 1 {
+                    ray_steps.push(Point { x, y });
+                }
+            }
+        }
+
+        AsteroidField {
+            asteroids: occupied
+                .iter()
+                .filter(|(_, &is_occupied)| is_occupied)
+                .map(|(point, _)| point)
+                .collect(),
+            occupied,
+            ray_steps,
+        }
+    }
+
+    /// The asteroids with a direct line of sight to the point, which needn't be an asteroid
+    /// but must be inside the map: every ray from outside leaves it on the first step.
+    pub fn visible_from<'a>(&'a self, point: &Point) -> impl Iterator<Item = Point> + 'a {
+        let start = *point;
+        self.ray_steps.iter().filter_map(move |step| {
+            let mut current = start;
+            loop {
+                current = Point {
+                    x: current.x + step.x,
+                    y: current.y + step.y,
+                };
+                match self.occupied.get(&current) {
+                    None => return None,
+                    Some(true) => return Some(current),
+                    Some(false) => {}
+                }
+            }
+        })
+    }
 
-        if max_detectable < num_detectable {
-            max_detectable = num_detectable;
-            max_point = *curr;
+    /// How many asteroids each asteroid can see; empty space has no count.
+    pub fn visibility_map(&self) -> Grid<Option<usize>> {
+        let mut counts = Grid::new(self.occupied.width(), self.occupied.height(), None);
+        for asteroid in self.asteroids.iter() {
+            *counts.get_mut(asteroid).unwrap() = Some(self.visible_from(asteroid).count());
         }
+
+        counts
+    }
+
+    /// Every asteroid as a station candidate, best first. Ties go to the topmost, then
+    /// leftmost, asteroid.
+    pub fn ranked_stations(&self) -> Vec<(Point, usize)> {
+        let mut ranked: Vec<(Point, usize)> = self
+            .visibility_map()
+            .iter()
+            .filter_map(|(point, count)| count.map(|count| (point, count)))
+            .collect();
+        ranked.sort_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then(a.y.cmp(&b.y)).then(a.x.cmp(&b.x))
+        });
+
+        ranked
     }
 
-    (max_point, max_detectable)
+    /// Shades each asteroid from `.` (sees the fewest) to `@` (sees the most).
+    pub fn render_heatmap(&self) -> String {
+        const SHADES: [char; 9] = ['.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+        let counts = self.visibility_map();
+        let present = || counts.iter().filter_map(|(_, count)| *count);
+        let min = present().min().unwrap_or(0);
+        let max = present().max().unwrap_or(0);
+
+        counts.render(|count| match count {
+            None => ' ',
+            Some(_) if max == min => SHADES[SHADES.len() - 1],
+            Some(count) => SHADES[(count - min) * (SHADES.len() - 1) / (max - min)],
+        })
+    }
 }
 
 fn get_vectors(asteroids: &HashSet<Point>, current: &Point) -> Vec<Vector> {
@@ -60,46 +150,116 @@ fn get_vectors(asteroids: &HashSet<Point>, current: &Point) -> Vec<Vector> {
 
 #[aoc(day10, part2)]
 pub fn part2(asteroids: &HashSet<Point>) -> i32 {
-    let (station, num_asteroids_detected) = find_max_station(&asteroids);
-    let mut vectors = get_vectors(&asteroids, &station);
-    vectors.sort_by(|a, b| {
-        if a.angle_deg == b.angle_deg {
-            // close to far
-            return a.distance.cmp(&b.distance);
+    let (station, _) = find_max_station(asteroids);
+    let target_point = VaporizationOrder::laser(asteroids, &station)
+        .nth(199)
+        .unwrap();
+
+    target_point.x * 100 + target_point.y
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rotation {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Yields asteroids in the order a rotating laser at the station destroys them. Each
+/// rotation destroys the closest remaining asteroid on every line of sight.
+pub struct VaporizationOrder {
+    // one queue per line of sight, in sweep order, each sorted close to far
+    lines_of_sight: Vec<VecDeque<Point>>,
+    next_line: usize,
+}
+
+impl VaporizationOrder {
+    /// Angles use the same convention as `Vector`: 0 degrees points right and 90 points up.
+    pub fn new(
+        asteroids: &HashSet<Point>,
+        station: &Point,
+        start_angle_deg: f64,
+        rotation: Rotation,
+    ) -> Self {
+        let mut by_step = HashMap::<Point, Vec<Vector>>::new();
+        for vector in get_vectors(asteroids, station) {
+            by_step
+                .entry(station.step_towards(&vector.end_point))
+                .or_default()
+                .push(vector);
         }
-        // high to low b/c we want to sweep clockwise
-        return b.angle_deg.cmp(&a.angle_deg);
-    });
 
-    let (first_quadrant, mut others): (Vec<Vector>, Vec<Vector>) = vectors
-        .iter()
-        .partition(|&v| v.angle_deg <= OrderedFloat(90f64));
-
-    let mut all_points = first_quadrant;
-    all_points.append(&mut others);
-
-    let mut destroyed_points: Vec<Point> = Vec::with_capacity(num_asteroids_detected);
-    let mut angles_covered_this_loop = Vec::<OrderedFloat<f64>>::new();
-
-    loop {
-        angles_covered_this_loop.clear();
-        for asteroid_vector in all_points.iter() {
-            if !angles_covered_this_loop.contains(&asteroid_vector.angle_deg)
-                && !destroyed_points.contains(&asteroid_vector.end_point)
-            {
-                destroyed_points.push(asteroid_vector.end_point);
-                angles_covered_this_loop.push(asteroid_vector.angle_deg);
-            }
+        let mut lines_of_sight: Vec<(OrderedFloat<f64>, VecDeque<Point>)> = by_step
+            .into_values()
+            .map(|mut vectors| {
+                vectors.sort_by_key(|vector| vector.distance);
+                let angle = vectors[0].angle_deg.into_inner();
+                let sweep = match rotation {
+                    Rotation::Clockwise => start_angle_deg - angle,
+                    Rotation::CounterClockwise => angle - start_angle_deg,
+                };
+                (
+                    OrderedFloat(sweep.rem_euclid(360f64)),
+                    vectors.iter().map(|vector| vector.end_point).collect(),
+                )
+            })
+            .collect();
+        lines_of_sight.sort_by_key(|(sweep, _)| *sweep);
+
+        VaporizationOrder {
+            lines_of_sight: lines_of_sight.into_iter().map(|(_, line)| line).collect(),
+            next_line: 0,
         }
+    }
+
+    /// The puzzle's laser: starts pointing up and rotates clockwise.
+    pub fn laser(asteroids: &HashSet<Point>, station: &Point) -> Self {
+        VaporizationOrder::new(asteroids, station, 90f64, Rotation::Clockwise)
+    }
 
-        if destroyed_points.len() >= 200 {
-            break;
+    /// The remaining asteroids grouped by the rotation of the laser that destroys them.
+    pub fn rotations(mut self) -> Vec<Vec<Point>> {
+        // lines before next_line have already been swept during the current rotation
+        let num_swept = self.next_line % self.lines_of_sight.len().max(1);
+        self.lines_of_sight.rotate_left(num_swept);
+        let num_unswept = self.lines_of_sight.len() - num_swept;
+
+        let mut rotations: Vec<Vec<Point>> = Vec::new();
+        for (line_index, line) in self.lines_of_sight.into_iter().enumerate() {
+            let first_rotation = if line_index < num_unswept { 0 } else { 1 };
+            for (index, point) in line.into_iter().enumerate() {
+                let rotation = first_rotation + index;
+                if rotations.len() <= rotation {
+                    rotations.resize_with(rotation + 1, Vec::new);
+                }
+                rotations[rotation].push(point);
+            }
         }
+
+        rotations
     }
+}
 
-    let target_point = destroyed_points[199];
+impl Iterator for VaporizationOrder {
+    type Item = Point;
 
-    target_point.x * 100 + target_point.y
+    fn next(&mut self) -> Option<Point> {
+        if self.lines_of_sight.is_empty() {
+            return None;
+        }
+        if self.next_line >= self.lines_of_sight.len() {
+            self.next_line = 0;
+        }
+
+        let line = &mut self.lines_of_sight[self.next_line];
+        let destroyed = line.pop_front();
+        if line.is_empty() {
+            self.lines_of_sight.remove(self.next_line);
+        } else {
+            self.next_line += 1;
+        }
+
+        destroyed
+    }
 }
 
 #[cfg(test)]
@@ -107,6 +267,14 @@ mod asteroids_find_max_station {
     use super::*;
     use indoc::indoc;
 
+    #[test]
+    fn empty_field() {
+        assert_eq!(
+            find_max_station(&get_occupied_points("...\n...")),
+            (Point::origin(), 0)
+        );
+    }
+
     #[test]
     fn day10_example1() {
         assert_eq!(
@@ -207,3 +375,234 @@ mod asteroids_find_max_station {
         );
     }
 }
+
+#[cfg(test)]
+mod vaporization_order {
+    use super::*;
+    use indoc::indoc;
+
+    fn large_example() -> HashSet<Point> {
+        get_occupied_points(&indoc!(
+            ".#..##.###...#######
+            ##.############..##.
+            .#.######.########.#
+            .###.#######.####.#.
+            #####.##.#.##.###.##
+            ..#####..#.#########
+            ####################
+            #.####....###.#.#.##
+            ##.#################
+            #####.##.###..####..
+            ..######..##.#######
+            ####.##.####...##..#
+            .#####..#.######.###
+            ##...#.##########...
+            #.##########.#######
+            .####.#.###.###.#.##
+            ....##.##.###..#####
+            .#.#.###########.###
+            #.#.#.#####.####.###
+            ###.##.####.##.#..##"
+        ))
+    }
+
+    fn small_square() -> HashSet<Point> {
+        get_occupied_points(&indoc!(
+            "###
+            ###
+            ###"
+        ))
+    }
+
+    #[test]
+    fn first_rotation_example() {
+        let asteroids = get_occupied_points(&indoc!(
+            ".#....#####...#..
+            ##...##.#####..##
+            ##...#...#.#####.
+            ..#.....#...###..
+            ..#.#.....#....##"
+        ));
+        assert_eq!(
+            VaporizationOrder::laser(&asteroids, &Point { x: 8, y: 3 })
+                .take(9)
+                .collect::<Vec<Point>>(),
+            vec![
+                Point { x: 8, y: 1 },
+                Point { x: 9, y: 0 },
+                Point { x: 9, y: 1 },
+                Point { x: 10, y: 0 },
+                Point { x: 9, y: 2 },
+                Point { x: 11, y: 1 },
+                Point { x: 12, y: 1 },
+                Point { x: 11, y: 2 },
+                Point { x: 15, y: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn large_example_order() {
+        let order = VaporizationOrder::laser(&large_example(), &Point { x: 11, y: 13 })
+            .collect::<Vec<Point>>();
+
+        assert_eq!(order.len(), 299);
+        assert_eq!(order[0], Point { x: 11, y: 12 });
+        assert_eq!(order[1], Point { x: 12, y: 1 });
+        assert_eq!(order[2], Point { x: 12, y: 2 });
+        assert_eq!(order[9], Point { x: 12, y: 8 });
+        assert_eq!(order[19], Point { x: 16, y: 0 });
+        assert_eq!(order[49], Point { x: 16, y: 9 });
+        assert_eq!(order[99], Point { x: 10, y: 16 });
+        assert_eq!(order[198], Point { x: 9, y: 6 });
+        assert_eq!(order[199], Point { x: 8, y: 2 });
+        assert_eq!(order[200], Point { x: 10, y: 9 });
+        assert_eq!(order[298], Point { x: 11, y: 1 });
+    }
+
+    #[test]
+    fn part2_large_example() {
+        assert_eq!(part2(&large_example()), 802);
+    }
+
+    #[test]
+    fn counter_clockwise() {
+        assert_eq!(
+            VaporizationOrder::new(
+                &small_square(),
+                &Point { x: 1, y: 1 },
+                90f64,
+                Rotation::CounterClockwise
+            )
+            .collect::<Vec<Point>>(),
+            vec![
+                Point { x: 1, y: 0 },
+                Point { x: 0, y: 0 },
+                Point { x: 0, y: 1 },
+                Point { x: 0, y: 2 },
+                Point { x: 1, y: 2 },
+                Point { x: 2, y: 2 },
+                Point { x: 2, y: 1 },
+                Point { x: 2, y: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn start_angle() {
+        assert_eq!(
+            VaporizationOrder::new(
+                &small_square(),
+                &Point { x: 1, y: 1 },
+                0f64,
+                Rotation::Clockwise
+            )
+            .take(3)
+            .collect::<Vec<Point>>(),
+            vec![
+                Point { x: 2, y: 1 },
+                Point { x: 2, y: 2 },
+                Point { x: 1, y: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn rotations() {
+        let asteroids = get_occupied_points(&indoc!(
+            "#.#.#
+            .....
+            #.#.#"
+        ));
+        let mut order = VaporizationOrder::laser(&asteroids, &Point { x: 0, y: 2 });
+        assert_eq!(order.next(), Some(Point { x: 0, y: 0 }));
+
+        assert_eq!(
+            order.rotations(),
+            vec![
+                vec![
+                    Point { x: 2, y: 0 },
+                    Point { x: 4, y: 0 },
+                    Point { x: 2, y: 2 },
+                ],
+                vec![Point { x: 4, y: 2 }],
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod asteroid_field {
+    use super::*;
+    use indoc::indoc;
+
+    fn example() -> AsteroidField {
+        AsteroidField::new(&get_occupied_points(&indoc!(
+            ".#..#
+            .....
+            #####
+            ....#
+            ...##"
+        )))
+    }
+
+    #[test]
+    fn visibility_map() {
+        assert_eq!(
+            example()
+                .visibility_map()
+                .render(|count| count.map_or('.', |count| (b'0' + count as u8) as char)),
+            ".7..7\n.....\n67775\n....7\n...87\n"
+        );
+    }
+
+    #[test]
+    fn ranked_stations() {
+        assert_eq!(
+            example().ranked_stations(),
+            vec![
+                (Point { x: 3, y: 4 }, 8),
+                (Point { x: 1, y: 0 }, 7),
+                (Point { x: 4, y: 0 }, 7),
+                (Point { x: 1, y: 2 }, 7),
+                (Point { x: 2, y: 2 }, 7),
+                (Point { x: 3, y: 2 }, 7),
+                (Point { x: 4, y: 3 }, 7),
+                (Point { x: 4, y: 4 }, 7),
+                (Point { x: 0, y: 2 }, 6),
+                (Point { x: 4, y: 2 }, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn visible_from_empty_space() {
+        let mut visible = example()
+            .visible_from(&Point { x: 2, y: 1 })
+            .collect::<Vec<Point>>();
+        visible.sort_by_key(|point| (point.y, point.x));
+
+        assert_eq!(
+            visible,
+            vec![
+                Point { x: 1, y: 0 },
+                Point { x: 4, y: 0 },
+                Point { x: 0, y: 2 },
+                Point { x: 1, y: 2 },
+                Point { x: 2, y: 2 },
+                Point { x: 3, y: 2 },
+                Point { x: 4, y: 2 },
+                Point { x: 3, y: 4 },
+                Point { x: 4, y: 4 },
+            ]
+        );
+    }
+
+    #[test]
+    fn render_heatmap() {
+        assert_eq!(
+            example().render_heatmap(),
+            " *  *\n     \n-***.\n    *\n   @*\n"
+        );
+    }
+}
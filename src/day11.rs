@@ -1,109 +1,299 @@
-use crate::grid::{Area, Point};
+use crate::grid::{render_area, Area, Direction, Point, Turn, Turtle, YAxis};
 use crate::intcode_computer::{IntcodeComputer, ProgramOutput};
 use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::{HashMap, HashSet};
 use std::num::ParseIntError;
 
-enum Color {
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Color {
     Black,
     White,
 }
 
 impl Color {
-    fn to_computer_input(&self) -> i64 {
+    fn to_computer_input(self) -> i64 {
         match self {
             Color::Black => 0,
             Color::White => 1,
         }
     }
+
+    fn from_computer_output(output: i64) -> Self {
+        match output {
+            0 => Color::Black,
+            1 => Color::White,
+            _ => panic!("Unexpected output value"),
+        }
+    }
 }
 
-#[derive(PartialEq, Eq, Debug)]
-enum FacingDirection {
-    Up,
-    Down,
-    Left,
-    Right,
+fn turn_from_output(output: i64) -> Turn {
+    match output {
+        0 => Turn::Left,
+        1 => Turn::Right,
+        _ => panic!("Bad turn direction"),
+    }
 }
 
-enum TurnDirection {
-    Left,
-    Right,
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HullEvent {
+    Paint {
+        step: usize,
+        position: Point,
+        color: Color,
+    },
+    Move {
+        step: usize,
+        position: Point,
+        heading: Direction,
+    },
 }
 
-impl From<i64> for TurnDirection {
-    fn from(direction: i64) -> Self {
-        match direction {
-            0 => TurnDirection::Left,
-            1 => TurnDirection::Right,
-            _ => panic!("Bad turn direction"),
+impl HullEvent {
+    pub fn step(&self) -> usize {
+        match self {
+            HullEvent::Paint { step, .. } | HullEvent::Move { step, .. } => *step,
         }
     }
 }
 
-struct Robot {
-    location: Point,
-    facing: FacingDirection,
+/// Runs the painting robot's program one step at a time (read the camera, paint, turn
+/// and move), keeping a log of everything it does.
+pub struct HullPainter {
+    computer: IntcodeComputer,
+    robot: Turtle,
+    starting_hull: HashMap<Point, Color>,
+    hull: HashMap<Point, Color>,
+    events: Vec<HullEvent>,
+    steps: usize,
+    halted: bool,
 }
 
-impl Robot {
-    fn new() -> Self {
-        Robot {
-            location: Point::origin(),
-            facing: FacingDirection::Up,
+impl HullPainter {
+    pub fn new(program: &[i64], starting_panel: Color) -> Self {
+        let mut hull = HashMap::new();
+        hull.insert(Point::origin(), starting_panel);
+        HullPainter {
+            computer: IntcodeComputer::yielding_computer(program),
+            // the hull is drawn with y increasing downwards, so the robot's "up" is negative y
+            robot: Turtle::new(Point::origin(), Direction::Up, YAxis::Down),
+            starting_hull: hull.clone(),
+            hull,
+            events: Vec::new(),
+            steps: 0,
+            halted: false,
+        }
+    }
+
+    /// Every panel the robot has been on, and the colour it is now.
+    pub fn hull(&self) -> &HashMap<Point, Color> {
+        &self.hull
+    }
+
+    pub fn robot(&self) -> &Turtle {
+        &self.robot
+    }
+
+    pub fn events(&self) -> &[HullEvent] {
+        &self.events
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Runs one step. Returns `false` once the program has halted.
+    pub fn step(&mut self) -> bool {
+        if self.halted {
+            return false;
         }
+        let step = self.steps + 1;
+
+        let panel = self.hull.entry(self.robot.position).or_insert(Color::Black);
+        self.computer.add_input(panel.to_computer_input());
+        match self.computer.run_program() {
+            ProgramOutput::NeedsInput => panic!("Robot asked for a second camera reading"),
+            ProgramOutput::Complete(_) => {
+                self.halted = true;
+                return false;
+            }
+            ProgramOutput::Yielded(val) => {
+                let color = Color::from_computer_output(val);
+                *panel = color;
+                self.events.push(HullEvent::Paint {
+                    step,
+                    position: self.robot.position,
+                    color,
+                });
+                self.steps = step;
+            }
+        };
+
+        match self.computer.run_program() {
+            ProgramOutput::NeedsInput => panic!("Robot asked for a camera reading mid-move"),
+            ProgramOutput::Complete(_) => {
+                self.halted = true;
+                false
+            }
+            ProgramOutput::Yielded(val) => {
+                self.robot.turn(turn_from_output(val));
+                self.robot.step();
+                self.hull.entry(self.robot.position).or_insert(Color::Black);
+                self.events.push(HullEvent::Move {
+                    step,
+                    position: self.robot.position,
+                    heading: self.robot.heading,
+                });
+                true
+            }
+        }
+    }
+
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+
+    /// The panels that have been painted at least once.
+    pub fn painted_panels(&self) -> HashSet<Point> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                HullEvent::Paint { position, .. } => Some(*position),
+                HullEvent::Move { .. } => None,
+            })
+            .collect()
     }
 
-    fn turn(&mut self, turn: TurnDirection) {
-        self.facing = match self.facing {
-            FacingDirection::Up => match turn {
-                TurnDirection::Left => FacingDirection::Left,
-                TurnDirection::Right => FacingDirection::Right,
-            },
-            FacingDirection::Down => match turn {
-                TurnDirection::Left => FacingDirection::Right,
-                TurnDirection::Right => FacingDirection::Left,
-            },
-            FacingDirection::Left => match turn {
-                TurnDirection::Left => FacingDirection::Down,
-                TurnDirection::Right => FacingDirection::Up,
-            },
-            FacingDirection::Right => match turn {
-                TurnDirection::Left => FacingDirection::Up,
-                TurnDirection::Right => FacingDirection::Down,
-            },
+    /// The hull as it is now, with `#` for white panels.
+    pub fn render(&self) -> String {
+        let covered_area = Area::from_point_list(&self.hull.keys().collect());
+        render_area(&covered_area, |point| match self.hull.get(point) {
+            None | Some(Color::Black) => ' ',
+            Some(Color::White) => '#',
+        })
+    }
+
+    /// A frame for the start of the run and one after each step, all drawn over the
+    /// whole area the robot ends up covering.
+    pub fn replay(&self) -> Replay<'_> {
+        Replay {
+            events: &self.events,
+            area: Area::from_point_list(&self.hull.keys().collect()),
+            hull: self.starting_hull.clone(),
+            robot: Turtle::new(Point::origin(), Direction::Up, YAxis::Down),
+            next_event: 0,
+            next_frame: 0,
+            last_frame: self.steps,
         }
     }
 
-    fn walk(&mut self) {
-        let current_location = self.location;
+    pub fn stats(&self) -> PaintStats {
+        let mut paint_counts = HashMap::new();
+        let mut area = Area::around(&Point::origin());
+        let mut bounds = vec![area];
 
-        match self.facing {
-            FacingDirection::Up => {
-                self.location = Point {
-                    x: current_location.x,
-                    y: current_location.y + 1,
+        for event in self.events.iter() {
+            match event {
+                HullEvent::Paint { position, .. } => {
+                    *paint_counts.entry(*position).or_insert(0) += 1;
+                    area.expand_to(position);
                 }
+                HullEvent::Move { position, .. } => area.expand_to(position),
             }
-            FacingDirection::Down => {
-                self.location = Point {
-                    x: current_location.x,
-                    y: current_location.y - 1,
-                }
+            if bounds.len() <= event.step() {
+                bounds.push(area);
+            } else {
+                bounds[event.step()] = area;
             }
-            FacingDirection::Left => {
-                self.location = Point {
-                    x: current_location.x - 1,
-                    y: current_location.y,
+        }
+
+        PaintStats {
+            repainted_panels: paint_counts.values().filter(|&&count| count > 1).count(),
+            repaints: paint_counts.values().map(|&count| count - 1).sum(),
+            paint_counts,
+            bounds,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PaintStats {
+    /// How many times each panel was painted.
+    pub paint_counts: HashMap<Point, usize>,
+    /// How many panels were painted more than once.
+    pub repainted_panels: usize,
+    /// How many paints landed on a panel that had already been painted.
+    pub repaints: usize,
+    /// The smallest area covering every panel the robot had been on, before the first
+    /// step and after each one.
+    pub bounds: Vec<Area>,
+}
+
+/// Rebuilds a painter's run from its event log, drawing white panels as `#`, black
+/// panels as `.` and the robot as an arrow.
+pub struct Replay<'a> {
+    events: &'a [HullEvent],
+    area: Area,
+    hull: HashMap<Point, Color>,
+    robot: Turtle,
+    next_event: usize,
+    next_frame: usize,
+    last_frame: usize,
+}
+
+impl<'a> Replay<'a> {
+    fn render_frame(&self) -> String {
+        render_area(&self.area, |point| {
+            if *point == self.robot.position {
+                match self.robot.heading {
+                    Direction::Up => '^',
+                    Direction::Right => '>',
+                    Direction::Down => 'v',
+                    Direction::Left => '<',
+                }
+            } else {
+                match self.hull.get(point) {
+                    Some(Color::White) => '#',
+                    None | Some(Color::Black) => '.',
                 }
             }
-            FacingDirection::Right => {
-                self.location = Point {
-                    x: current_location.x + 1,
-                    y: current_location.y,
+        })
+    }
+}
+
+impl<'a> Iterator for Replay<'a> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        if self.next_frame > self.last_frame {
+            return None;
+        }
+        while let Some(event) = self.events.get(self.next_event) {
+            if event.step() > self.next_frame {
+                break;
+            }
+            match *event {
+                HullEvent::Paint {
+                    position, color, ..
+                } => {
+                    self.hull.insert(position, color);
+                }
+                HullEvent::Move {
+                    position, heading, ..
+                } => {
+                    self.robot.position = position;
+                    self.robot.heading = heading;
                 }
             }
-        };
+            self.next_event += 1;
+        }
+        self.next_frame += 1;
+
+        Some(self.render_frame())
     }
 }
 
@@ -114,62 +304,92 @@ pub fn get_program(input: &str) -> Result<Vec<i64>, ParseIntError> {
 
 #[aoc(day11, part1)]
 pub fn part1(program_input: &[i64]) -> usize {
-    let mut grid = HashMap::<Point, Color>::new();
-    run_robot(&program_input, &mut grid).len()
+    let mut painter = HullPainter::new(program_input, Color::Black);
+    painter.run();
+    painter.painted_panels().len()
 }
 
 #[aoc(day11, part2)]
 pub fn part2(program_input: &[i64]) -> String {
-    let mut grid = HashMap::<Point, Color>::new();
-    grid.insert(Point::origin(), Color::White);
-
-    run_robot(&program_input, &mut grid);
-    let covered_area = Area::from_point_list(&grid.keys().collect());
+    let mut painter = HullPainter::new(program_input, Color::White);
+    painter.run();
+    format!("\n{}", painter.render())
+}
 
-    let mut result = "\n".to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
 
-    for y in covered_area.min_y..=covered_area.max_y {
-        for x in covered_area.min_x..=covered_area.max_x {
-            result.push_str(match grid.get(&Point { x: x, y: y }) {
-                None | Some(Color::Black) => " ",
-                Some(Color::White) => "#",
-            });
-        }
-        result.push_str("\n");
+    /// A program that ignores the camera and makes the moves from the puzzle's example.
+    fn example_program() -> Vec<i64> {
+        let outputs = [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)];
+        let mut program: Vec<i64> = outputs
+            .iter()
+            .flat_map(|&(color, turn)| vec![3, 100, 104, color, 104, turn])
+            .collect();
+        program.push(99);
+        program
     }
 
-    result
-}
-
-fn run_robot(program_input: &[i64], grid: &mut HashMap<Point, Color>) -> HashSet<Point> {
-    let mut painted_points = HashSet::<Point>::new();
-    let mut robot = Robot::new();
-    let mut computer = IntcodeComputer::yielding_computer(&program_input);
-
-    loop {
-        let robot_location = grid.entry(robot.location).or_insert(Color::Black);
-        computer.add_input(robot_location.to_computer_input());
+    fn example_painter() -> HullPainter {
+        let mut painter = HullPainter::new(&example_program(), Color::Black);
+        painter.run();
+        painter
+    }
 
-        match computer.run_program() {
-            ProgramOutput::Complete(_) => break,
-            ProgramOutput::Yielded(val) => {
-                painted_points.insert(robot.location);
-                match val {
-                    0 => *robot_location = Color::Black,
-                    1 => *robot_location = Color::White,
-                    _ => panic!("Unexpected output value"),
+    #[test]
+    fn paints_example() {
+        let painter = example_painter();
+        assert!(painter.is_halted());
+        assert_eq!(painter.steps(), 7);
+        assert_eq!(painter.painted_panels().len(), 6);
+        assert_eq!(painter.robot().position, Point { x: 0, y: -1 });
+        assert_eq!(painter.robot().heading, Direction::Left);
+        assert_eq!(
+            painter.events()[..2],
+            [
+                HullEvent::Paint {
+                    step: 1,
+                    position: Point::origin(),
+                    color: Color::White
+                },
+                HullEvent::Move {
+                    step: 1,
+                    position: Point { x: -1, y: 0 },
+                    heading: Direction::Left
                 }
-            }
-        };
+            ]
+        );
+    }
 
-        match computer.run_program() {
-            ProgramOutput::Complete(_) => break,
-            ProgramOutput::Yielded(val) => {
-                robot.turn(TurnDirection::from(val));
-                robot.walk();
-            }
-        }
+    #[test]
+    fn replay() {
+        let frames: Vec<String> = example_painter().replay().collect();
+        assert_eq!(frames.len(), 8);
+        assert_eq!(frames[0], "...\n.^.\n...\n");
+        assert_eq!(frames[1], "...\n<#.\n...\n");
+        assert_eq!(frames[4], "...\n.^.\n##.\n");
+        assert_eq!(
+            frames[7],
+            indoc!(
+                ".<#
+                ..#
+                ##.
+                "
+            )
+        );
     }
 
-    painted_points
+    #[test]
+    fn stats() {
+        let stats = example_painter().stats();
+        assert_eq!(stats.paint_counts.len(), 6);
+        assert_eq!(stats.paint_counts[&Point::origin()], 2);
+        assert_eq!((stats.repainted_panels, stats.repaints), (1, 1));
+        assert_eq!(stats.bounds.len(), 8);
+        assert_eq!(stats.bounds[0], Area::new(0, 0, 0, 0));
+        assert_eq!(stats.bounds[2], Area::new(-1, 0, 0, 1));
+        assert_eq!(stats.bounds[7], Area::new(-1, -1, 1, 1));
+    }
 }
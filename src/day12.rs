@@ -1,80 +1,168 @@
+use crate::grid::Point3;
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use regex::Regex;
-use std::cell::RefCell;
-use std::collections::hash_map::DefaultHasher;
-use std::collections::HashSet;
-use std::hash::{Hash, Hasher};
+use std::fmt::Write;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct ThreeDimensionalValue {
-    x: i32,
-    y: i32,
-    z: i32,
-}
-
-impl ThreeDimensionalValue {
-    fn zeros() -> Self {
-        ThreeDimensionalValue { x: 0, y: 0, z: 0 }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Moon {
-    position: ThreeDimensionalValue,
-    velocity: ThreeDimensionalValue,
+    pub position: Point3,
+    pub velocity: Point3,
 }
 
 impl Moon {
+    fn at_rest(position: Point3) -> Self {
+        Moon {
+            position,
+            velocity: Point3::zeros(),
+        }
+    }
+
     fn apply_velocity(&mut self) {
-        self.position.x += self.velocity.x;
-        self.position.y += self.velocity.y;
-        self.position.z += self.velocity.z;
+        self.position += self.velocity;
     }
 
-    fn potential_energy(&self) -> i32 {
-        self.position.x.abs() + self.position.y.abs() + self.position.z.abs()
+    pub fn potential_energy(&self) -> i32 {
+        self.position.manhattan_norm()
     }
 
-    fn kinetic_energy(&self) -> i32 {
-        self.velocity.x.abs() + self.velocity.y.abs() + self.velocity.z.abs()
+    pub fn kinetic_energy(&self) -> i32 {
+        self.velocity.manhattan_norm()
+    }
+
+    pub fn total_energy(&self) -> i32 {
+        self.potential_energy() * self.kinetic_energy()
     }
 }
 
-#[derive(PartialEq, Eq)]
+/// Any number of moons, each pulled one unit per step towards every other moon on
+/// each axis.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct MoonSystem {
-    io: RefCell<Moon>,
-    europa: RefCell<Moon>,
-    ganymede: RefCell<Moon>,
-    callisto: RefCell<Moon>,
+    moons: Vec<Moon>,
 }
 
 impl MoonSystem {
-    fn len(&self) -> usize {
-        4
+    pub fn new(positions: &[Point3]) -> Self {
+        MoonSystem {
+            moons: positions
+                .iter()
+                .map(|&position| Moon::at_rest(position))
+                .collect(),
+        }
+    }
+
+    pub fn moons(&self) -> &[Moon] {
+        &self.moons
     }
 
-    fn get_by_index(&self, index: usize) -> &RefCell<Moon> {
-        match index {
-            0 => &self.io,
-            1 => &self.europa,
-            2 => &self.ganymede,
-            3 => &self.callisto,
-            _ => panic!("No moon at index"),
+    pub fn step(&mut self) {
+        self.update_velocities();
+        for moon in self.moons.iter_mut() {
+            moon.apply_velocity();
         }
     }
 
-    fn as_vec(&self) -> Vec<&RefCell<Moon>> {
-        vec![&self.io, &self.europa, &self.ganymede, &self.callisto]
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
     }
-}
 
-impl Hash for MoonSystem {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.io.borrow().hash(state);
-        self.europa.borrow().hash(state);
-        self.ganymede.borrow().hash(state);
-        self.callisto.borrow().hash(state);
+    /// Runs the system, returning the position of every moon before the first step
+    /// and after each one.
+    pub fn record_trajectory(&mut self, steps: usize) -> Vec<Vec<Point3>> {
+        let mut trajectory = Vec::with_capacity(steps + 1);
+        trajectory.push(self.positions());
+        for _ in 0..steps {
+            self.step();
+            trajectory.push(self.positions());
+        }
+
+        trajectory
+    }
+
+    fn positions(&self) -> Vec<Point3> {
+        self.moons.iter().map(|moon| moon.position).collect()
+    }
+
+    pub fn total_energy(&self) -> i32 {
+        self.moons.iter().map(Moon::total_energy).sum()
+    }
+
+    /// A breakdown of each moon's energy, in the same layout the puzzle uses.
+    pub fn energy_report(&self) -> String {
+        let mut report = String::new();
+        for moon in self.moons.iter() {
+            writeln!(
+                report,
+                "pot: {} = {};   kin: {} = {};   total: {} * {} = {}",
+                moon.position.axes().map(|value| value.abs()).join(" + "),
+                moon.potential_energy(),
+                moon.velocity.axes().map(|value| value.abs()).join(" + "),
+                moon.kinetic_energy(),
+                moon.potential_energy(),
+                moon.kinetic_energy(),
+                moon.total_energy()
+            )
+            .unwrap();
+        }
+        writeln!(
+            report,
+            "Sum of total energy: {} = {}",
+            self.moons.iter().map(Moon::total_energy).join(" + "),
+            self.total_energy()
+        )
+        .unwrap();
+
+        report
+    }
+
+    /// The position and velocity of every moon along one axis. Axes never affect each
+    /// other, so each one can repeat on its own schedule.
+    fn axis_state(&self, axis: usize) -> Vec<(i32, i32)> {
+        self.moons
+            .iter()
+            .map(|moon| (moon.position[axis], moon.velocity[axis]))
+            .collect()
+    }
+
+    /// How many steps each axis takes to return to its current state. Every step can be
+    /// undone, so the first state an axis repeats is always the one it started in.
+    pub fn axis_periods(&self) -> Vec<u64> {
+        let num_axes = Point3::zeros().num_axes();
+        let initial_states: Vec<Vec<(i32, i32)>> =
+            (0..num_axes).map(|axis| self.axis_state(axis)).collect();
+        let mut periods: Vec<Option<u64>> = vec![None; num_axes];
+
+        let mut system = self.clone();
+        let mut steps: u64 = 0;
+        while periods.iter().any(Option::is_none) {
+            system.step();
+            steps += 1;
+            for (axis, period) in periods.iter_mut().enumerate() {
+                if period.is_none() && system.axis_state(axis) == initial_states[axis] {
+                    *period = Some(steps);
+                }
+            }
+        }
+
+        periods.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// How many steps the whole system takes to return to its current state.
+    pub fn period(&self) -> u128 {
+        self.axis_periods()
+            .into_iter()
+            .fold(1, |acc, period| lcm(acc, u128::from(period)))
+    }
+
+    fn update_velocities(&mut self) {
+        for (a_index, b_index) in (0..self.moons.len()).tuple_combinations() {
+            let pull = (self.moons[b_index].position - self.moons[a_index].position).signum();
+            self.moons[a_index].velocity += pull;
+            self.moons[b_index].velocity -= pull;
+        }
     }
 }
 
@@ -84,109 +172,160 @@ pub fn get_starting_positions(input: &str) -> MoonSystem {
         static ref PARSING_EXPR: Regex =
             Regex::new(r"^<x=(?P<x>-?\d+), y=(?P<y>-?\d+), z=(?P<z>-?\d+)>$").unwrap();
     }
-    let positions: Vec<ThreeDimensionalValue> = input
+    let positions: Vec<Point3> = input
         .lines()
         .map(|line| {
-            let caps = PARSING_EXPR.captures(line).unwrap();
-            ThreeDimensionalValue {
-                x: caps["x"].parse::<i32>().unwrap(),
-                y: caps["y"].parse::<i32>().unwrap(),
-                z: caps["z"].parse::<i32>().unwrap(),
-            }
+            let caps = PARSING_EXPR.captures(line.trim()).unwrap();
+            Point3::new(
+                caps["x"].parse::<i32>().unwrap(),
+                caps["y"].parse::<i32>().unwrap(),
+                caps["z"].parse::<i32>().unwrap(),
+            )
         })
         .collect();
 
-    return MoonSystem {
-        io: RefCell::new(Moon {
-            position: positions[0],
-            velocity: ThreeDimensionalValue::zeros(),
-        }),
-        europa: RefCell::new(Moon {
-            position: positions[1],
-            velocity: ThreeDimensionalValue::zeros(),
-        }),
-        ganymede: RefCell::new(Moon {
-            position: positions[2],
-            velocity: ThreeDimensionalValue::zeros(),
-        }),
-        callisto: RefCell::new(Moon {
-            position: positions[3],
-            velocity: ThreeDimensionalValue::zeros(),
-        }),
-    };
+    MoonSystem::new(&positions)
 }
 
 #[aoc(day12, part1)]
 pub fn part1(moons: &MoonSystem) -> i32 {
-    let ticks = 1000;
+    let mut system = moons.clone();
+    system.run(1000);
+    system.total_energy()
+}
 
-    for _ in 0..ticks {
-        update_velocities(&moons);
-        for moon in moons.as_vec().iter() {
-            moon.borrow_mut().apply_velocity();
-        }
+#[aoc(day12, part2)]
+pub fn part2(moons: &MoonSystem) -> u128 {
+    moons.period()
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
     }
+}
 
-    moons.as_vec().iter().fold(0, |acc, curr| {
-        let moon = curr.borrow();
-        acc + moon.potential_energy() * moon.kinetic_energy()
-    })
+fn lcm(a: u128, b: u128) -> u128 {
+    a / gcd(a, b) * b
 }
 
-fn update_velocities(moons: &MoonSystem) {
-    for moon_pair_indexes in (0..(moons.len())).combinations(2) {
-        let a_ref = moons.get_by_index(moon_pair_indexes[0]);
-        let b_ref = moons.get_by_index(moon_pair_indexes[1]);
-        let mut a = a_ref.borrow_mut();
-        let mut b = b_ref.borrow_mut();
-        if a.position.x < b.position.x {
-            a.velocity.x += 1;
-            b.velocity.x -= 1;
-        } else if b.position.x < a.position.x {
-            a.velocity.x -= 1;
-            b.velocity.x += 1;
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
 
-        if a.position.y < b.position.y {
-            a.velocity.y += 1;
-            b.velocity.y -= 1;
-        } else if b.position.y < a.position.y {
-            a.velocity.y -= 1;
-            b.velocity.y += 1;
-        }
+    fn example_1() -> MoonSystem {
+        get_starting_positions(&indoc!(
+            "<x=-1, y=0, z=2>
+            <x=2, y=-10, z=-7>
+            <x=4, y=-8, z=8>
+            <x=3, y=5, z=-1>"
+        ))
+    }
 
-        if a.position.z < b.position.z {
-            a.velocity.z += 1;
-            b.velocity.z -= 1;
-        } else if b.position.z < a.position.z {
-            a.velocity.z -= 1;
-            b.velocity.z += 1;
-        }
+    #[test]
+    fn first_step() {
+        let mut system = example_1();
+        system.step();
+        assert_eq!(
+            system.moons(),
+            &[
+                Moon {
+                    position: Point3::new(2, -1, 1),
+                    velocity: Point3::new(3, -1, -1)
+                },
+                Moon {
+                    position: Point3::new(3, -7, -4),
+                    velocity: Point3::new(1, 3, 3)
+                },
+                Moon {
+                    position: Point3::new(1, -7, 5),
+                    velocity: Point3::new(-3, 1, -3)
+                },
+                Moon {
+                    position: Point3::new(2, 2, 0),
+                    velocity: Point3::new(-1, -3, 1)
+                },
+            ]
+        );
     }
-}
 
-#[aoc(day12, part2)]
-pub fn part2(moons: &MoonSystem) -> usize {
-    let mut seen = HashSet::<u64>::new();
-    seen.insert(calculate_hash(&moons));
-
-    loop {
-        update_velocities(&moons);
-        for moon in moons.as_vec().iter() {
-            moon.borrow_mut().apply_velocity();
-        }
-        let curr_hash = calculate_hash(&moons);
-        if seen.contains(&curr_hash) {
-            break;
-        }
-        seen.insert(curr_hash);
+    #[test]
+    fn energy_after_10_steps() {
+        let mut system = example_1();
+        system.run(10);
+        assert_eq!(system.total_energy(), 179);
+        assert_eq!(
+            system.energy_report(),
+            indoc!(
+                "pot: 2 + 1 + 3 = 6;   kin: 3 + 2 + 1 = 6;   total: 6 * 6 = 36
+                pot: 1 + 8 + 0 = 9;   kin: 1 + 1 + 3 = 5;   total: 9 * 5 = 45
+                pot: 3 + 6 + 1 = 10;   kin: 3 + 2 + 3 = 8;   total: 10 * 8 = 80
+                pot: 2 + 0 + 4 = 6;   kin: 1 + 1 + 1 = 3;   total: 6 * 3 = 18
+                Sum of total energy: 36 + 45 + 80 + 18 = 179
+                "
+            )
+        );
     }
 
-    seen.len()
-}
+    #[test]
+    fn energy_after_100_steps() {
+        let mut system = get_starting_positions(&indoc!(
+            "<x=-8, y=-10, z=0>
+            <x=5, y=5, z=10>
+            <x=2, y=-7, z=3>
+            <x=9, y=-8, z=-3>"
+        ));
+        system.run(100);
+        assert_eq!(system.total_energy(), 1940);
+    }
+
+    #[test]
+    fn any_number_of_moons() {
+        let mut system = get_starting_positions(&indoc!(
+            "<x=0, y=0, z=0>
+            <x=2, y=0, z=-1>"
+        ));
+        assert_eq!(
+            system.record_trajectory(2),
+            vec![
+                vec![Point3::new(0, 0, 0), Point3::new(2, 0, -1)],
+                vec![Point3::new(1, 0, -1), Point3::new(1, 0, 0)],
+                vec![Point3::new(2, 0, -1), Point3::new(0, 0, 0)],
+            ]
+        );
+
+        let mut crowded = get_starting_positions(&indoc!(
+            "<x=-1, y=0, z=2>
+            <x=2, y=-10, z=-7>
+            <x=4, y=-8, z=8>
+            <x=3, y=5, z=-1>
+            <x=0, y=0, z=0>
+            <x=7, y=1, z=-2>"
+        ));
+        crowded.run(10);
+        assert_eq!(crowded.moons().len(), 6);
+    }
 
-fn calculate_hash<T: Hash>(t: &T) -> u64 {
-    let mut s = DefaultHasher::new();
-    t.hash(&mut s);
-    s.finish()
+    #[test]
+    fn axis_periods() {
+        let system = example_1();
+        assert_eq!(system.axis_periods(), vec![18, 28, 44]);
+        assert_eq!(system.period(), 2772);
+    }
+
+    #[test]
+    fn long_period() {
+        assert_eq!(
+            part2(&get_starting_positions(&indoc!(
+                "<x=-8, y=-10, z=0>
+                <x=5, y=5, z=10>
+                <x=2, y=-7, z=3>
+                <x=9, y=-8, z=-3>"
+            ))),
+            4686774924
+        );
+    }
 }
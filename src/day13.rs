@@ -0,0 +1,435 @@
+use crate::grid::{Grid, Point};
+use crate::intcode_computer::{IntcodeComputer, ProgramOutput};
+use aoc_runner_derive::{aoc, aoc_generator};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::num::ParseIntError;
+use std::time::Duration;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Block,
+    Paddle,
+    Ball,
+}
+
+impl From<i64> for Tile {
+    fn from(tile_id: i64) -> Self {
+        match tile_id {
+            0 => Tile::Empty,
+            1 => Tile::Wall,
+            2 => Tile::Block,
+            3 => Tile::Paddle,
+            4 => Tile::Ball,
+            _ => panic!("Bad tile id"),
+        }
+    }
+}
+
+impl Tile {
+    pub fn to_char(self) -> char {
+        match self {
+            Tile::Empty => ' ',
+            Tile::Wall => '#',
+            Tile::Block => '=',
+            Tile::Paddle => '-',
+            Tile::Ball => 'o',
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Joystick {
+    Left,
+    Neutral,
+    Right,
+}
+
+impl Joystick {
+    fn to_computer_input(self) -> i64 {
+        match self {
+            Joystick::Left => -1,
+            Joystick::Neutral => 0,
+            Joystick::Right => 1,
+        }
+    }
+}
+
+/// One (x, y, value) triple from the game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DrawCommand {
+    Tile {
+        position: Point,
+        tile: Tile,
+    },
+    /// Sent as x = -1, y = 0 and shown on the score display instead of the screen.
+    Score(i64),
+}
+
+impl DrawCommand {
+    pub fn decode(x: i64, y: i64, value: i64) -> Self {
+        match (x, y) {
+            (-1, 0) => DrawCommand::Score(value),
+            _ => DrawCommand::Tile {
+                position: Point {
+                    x: x as i32,
+                    y: y as i32,
+                },
+                tile: Tile::from(value),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArcadeState {
+    WaitingForJoystick,
+    GameOver,
+}
+
+/// The arcade cabinet: the game's program plus the screen and score display it draws on.
+#[derive(Clone)]
+pub struct Arcade {
+    computer: IntcodeComputer,
+    screen: Grid<Tile>,
+    score: i64,
+    ball: Option<Point>,
+    paddle: Option<Point>,
+    pending_output: Vec<i64>,
+}
+
+impl Arcade {
+    pub fn new(program: &[i64]) -> Self {
+        Arcade {
+            computer: IntcodeComputer::yielding_computer(program),
+            screen: Grid::new(0, 0, Tile::Empty),
+            score: 0,
+            ball: None,
+            paddle: None,
+            pending_output: Vec::with_capacity(3),
+        }
+    }
+
+    /// Puts two quarters in by patching the first memory address, so the game can be
+    /// played instead of just drawn.
+    pub fn free_play(program: &[i64]) -> Self {
+        let mut program = program.to_vec();
+        program[0] = 2;
+        Arcade::new(&program)
+    }
+
+    pub fn screen(&self) -> &Grid<Tile> {
+        &self.screen
+    }
+
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+
+    pub fn ball(&self) -> Option<Point> {
+        self.ball
+    }
+
+    pub fn paddle(&self) -> Option<Point> {
+        self.paddle
+    }
+
+    pub fn count_tiles(&self, tile: Tile) -> usize {
+        self.screen
+            .iter()
+            .filter(|&(_, &on_screen)| on_screen == tile)
+            .count()
+    }
+
+    /// Runs the game, drawing as it goes, until it wants to read the joystick or ends.
+    pub fn run(&mut self) -> ArcadeState {
+        loop {
+            match self.computer.run_program() {
+                ProgramOutput::Yielded(val) => {
+                    self.pending_output.push(val);
+                    if let [x, y, value] = self.pending_output[..] {
+                        self.pending_output.clear();
+                        self.draw(DrawCommand::decode(x, y, value));
+                    }
+                }
+                ProgramOutput::NeedsInput => {
+                    // everything drawn so far is on the screen, so the VM needn't keep it
+                    self.computer.clear_history();
+                    return ArcadeState::WaitingForJoystick;
+                }
+                ProgramOutput::Complete(_) => return ArcadeState::GameOver,
+            }
+        }
+    }
+
+    pub fn tilt_joystick(&mut self, joystick: Joystick) {
+        self.computer.add_input(joystick.to_computer_input());
+    }
+
+    /// Plays until the game ends, asking `controller` which way to tilt the joystick
+    /// whenever the game reads it. Returns the final score.
+    pub fn play<F>(&mut self, mut controller: F) -> i64
+    where
+        F: FnMut(&Arcade) -> Joystick,
+    {
+        loop {
+            match self.run() {
+                ArcadeState::GameOver => return self.score,
+                ArcadeState::WaitingForJoystick => {
+                    let joystick = controller(self);
+                    self.tilt_joystick(joystick);
+                }
+            }
+        }
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "Score: {}\n{}",
+            self.score,
+            self.screen.render(|tile| tile.to_char())
+        )
+    }
+
+    fn draw(&mut self, command: DrawCommand) {
+        match command {
+            DrawCommand::Score(score) => self.score = score,
+            DrawCommand::Tile { position, tile } => {
+                self.screen.expand_to(&position, Tile::Empty);
+                *self.screen.get_mut(&position).unwrap() = tile;
+                match tile {
+                    Tile::Ball => self.ball = Some(position),
+                    Tile::Paddle => self.paddle = Some(position),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the paddle underneath the ball.
+pub fn track_ball(arcade: &Arcade) -> Joystick {
+    match (arcade.ball(), arcade.paddle()) {
+        (Some(ball), Some(paddle)) => match ball.x.cmp(&paddle.x) {
+            Ordering::Less => Joystick::Left,
+            Ordering::Equal => Joystick::Neutral,
+            Ordering::Greater => Joystick::Right,
+        },
+        _ => Joystick::Neutral,
+    }
+}
+
+/// A game being played a frame at a time, where a frame ends each time the game reads
+/// the joystick. A snapshot of the whole cabinet is kept for each recent frame so play
+/// can be rewound.
+pub struct ArcadeSession {
+    snapshots: VecDeque<(usize, Arcade)>,
+    state: ArcadeState,
+    paused: bool,
+    frame_delay: Duration,
+}
+
+impl ArcadeSession {
+    /// How many frames back a session can rewind.
+    pub const HISTORY: usize = 2000;
+    const FASTEST: Duration = Duration::from_millis(10);
+    const SLOWEST: Duration = Duration::from_millis(640);
+
+    pub fn new(mut arcade: Arcade) -> Self {
+        let state = arcade.run();
+        let mut snapshots = VecDeque::with_capacity(ArcadeSession::HISTORY);
+        snapshots.push_back((0, arcade));
+        ArcadeSession {
+            snapshots,
+            state,
+            paused: false,
+            frame_delay: Duration::from_millis(80),
+        }
+    }
+
+    pub fn arcade(&self) -> &Arcade {
+        &self.snapshots.back().unwrap().1
+    }
+
+    pub fn frame(&self) -> usize {
+        self.snapshots.back().unwrap().0
+    }
+
+    pub fn state(&self) -> ArcadeState {
+        self.state
+    }
+
+    /// Plays one frame with the joystick held the given way. Does nothing once the game
+    /// is over.
+    pub fn advance(&mut self, joystick: Joystick) -> ArcadeState {
+        if self.state == ArcadeState::GameOver {
+            return self.state;
+        }
+        let mut arcade = self.arcade().clone();
+        arcade.tilt_joystick(joystick);
+        self.state = arcade.run();
+
+        if self.snapshots.len() == ArcadeSession::HISTORY {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back((self.frame() + 1, arcade));
+        self.state
+    }
+
+    /// Goes back up to `frames` frames, as far as the oldest snapshot kept.
+    pub fn rewind(&mut self, frames: usize) {
+        let kept = self.snapshots.len().saturating_sub(frames).max(1);
+        if kept < self.snapshots.len() {
+            self.snapshots.truncate(kept);
+            // every snapshot before the last was taken while waiting for the joystick
+            self.state = ArcadeState::WaitingForJoystick;
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// How long each frame stays on screen.
+    pub fn frame_delay(&self) -> Duration {
+        self.frame_delay
+    }
+
+    pub fn speed_up(&mut self) {
+        self.frame_delay = (self.frame_delay / 2).max(ArcadeSession::FASTEST);
+    }
+
+    pub fn slow_down(&mut self) {
+        self.frame_delay = (self.frame_delay * 2).min(ArcadeSession::SLOWEST);
+    }
+}
+
+#[aoc_generator(day13)]
+pub fn get_program(input: &str) -> Result<Vec<i64>, ParseIntError> {
+    input.split(',').map(|l| l.parse::<i64>()).collect()
+}
+
+#[aoc(day13, part1)]
+pub fn part1(program: &[i64]) -> usize {
+    let mut arcade = Arcade::new(program);
+    arcade.run();
+    arcade.count_tiles(Tile::Block)
+}
+
+#[aoc(day13, part2)]
+pub fn part2(program: &[i64]) -> i64 {
+    Arcade::free_play(program).play(track_ball)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Output instructions that draw each (x, y, value) triple.
+    fn draw_instructions(triples: &[(i64, i64, i64)]) -> Vec<i64> {
+        triples
+            .iter()
+            .flat_map(|&(x, y, value)| vec![104, x, 104, y, 104, value])
+            .collect()
+    }
+
+    #[test]
+    fn decodes_triples() {
+        assert_eq!(
+            DrawCommand::decode(1, 2, 3),
+            DrawCommand::Tile {
+                position: Point { x: 1, y: 2 },
+                tile: Tile::Paddle
+            }
+        );
+        assert_eq!(DrawCommand::decode(-1, 0, 12345), DrawCommand::Score(12345));
+    }
+
+    #[test]
+    fn counts_blocks() {
+        let mut program = draw_instructions(&[(0, 0, 1), (1, 0, 2), (2, 1, 2), (1, 0, 0)]);
+        program.extend(draw_instructions(&[(3, 1, 2), (-1, 0, 40)]));
+        program.push(99);
+
+        let mut arcade = Arcade::new(&program);
+        assert_eq!(arcade.run(), ArcadeState::GameOver);
+        assert_eq!(arcade.count_tiles(Tile::Block), 2);
+        assert_eq!(arcade.render(), "Score: 40\n#   \n  ==\n");
+    }
+
+    #[test]
+    fn follows_the_ball() {
+        // draws a paddle and a ball, then shows the joystick reading as the score
+        let mut program = draw_instructions(&[(1, 1, 3), (3, 0, 4)]);
+        program.extend(vec![3, 100, 104, -1, 104, 0, 4, 100, 99]);
+
+        let mut arcade = Arcade::new(&program);
+        assert_eq!(arcade.run(), ArcadeState::WaitingForJoystick);
+        assert_eq!(arcade.ball(), Some(Point { x: 3, y: 0 }));
+        assert_eq!(arcade.paddle(), Some(Point { x: 1, y: 1 }));
+        assert_eq!(track_ball(&arcade), Joystick::Right);
+
+        assert_eq!(Arcade::new(&program).play(track_ball), 1);
+        assert_eq!(Arcade::new(&program).play(|_| Joystick::Left), -1);
+    }
+
+    #[test]
+    fn session_rewinds() {
+        // shows each joystick reading as the score, three times over
+        let mut program = Vec::new();
+        for _ in 0..3 {
+            program.extend(vec![3, 100, 104, -1, 104, 0, 4, 100]);
+        }
+        program.push(99);
+
+        let mut session = ArcadeSession::new(Arcade::new(&program));
+        assert_eq!(session.frame(), 0);
+        assert_eq!(
+            session.advance(Joystick::Right),
+            ArcadeState::WaitingForJoystick
+        );
+        assert_eq!(
+            session.advance(Joystick::Left),
+            ArcadeState::WaitingForJoystick
+        );
+        assert_eq!(session.advance(Joystick::Neutral), ArcadeState::GameOver);
+        assert_eq!(session.advance(Joystick::Right), ArcadeState::GameOver);
+        assert_eq!((session.frame(), session.arcade().score()), (3, 0));
+
+        session.rewind(2);
+        assert_eq!(session.state(), ArcadeState::WaitingForJoystick);
+        assert_eq!((session.frame(), session.arcade().score()), (1, 1));
+        assert_eq!(
+            session.advance(Joystick::Right),
+            ArcadeState::WaitingForJoystick
+        );
+        assert_eq!((session.frame(), session.arcade().score()), (2, 1));
+
+        session.rewind(10);
+        assert_eq!((session.frame(), session.arcade().score()), (0, 0));
+    }
+
+    #[test]
+    fn session_speed() {
+        let mut session = ArcadeSession::new(Arcade::new(&[99]));
+        assert_eq!(session.state(), ArcadeState::GameOver);
+        session.rewind(1);
+        assert_eq!(session.state(), ArcadeState::GameOver);
+
+        for _ in 0..10 {
+            session.speed_up();
+        }
+        assert_eq!(session.frame_delay(), Duration::from_millis(10));
+        session.slow_down();
+        assert_eq!(session.frame_delay(), Duration::from_millis(20));
+
+        session.toggle_pause();
+        assert!(session.is_paused());
+    }
+}
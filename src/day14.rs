@@ -0,0 +1,707 @@
+use aoc_runner_derive::{aoc, aoc_generator};
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write;
+
+const ORE: &str = "ORE";
+const FUEL: &str = "FUEL";
+const CARGO_HOLD_ORE: u64 = 1_000_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChemicalId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ingredient {
+    pub chemical: ChemicalId,
+    pub quantity: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reaction {
+    pub inputs: Vec<Ingredient>,
+    pub output: Ingredient,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReactionError {
+    MalformedLine { line_number: usize, line: String },
+    DuplicateRecipe { chemical: String },
+    RecipeForOre { line_number: usize },
+    UnknownChemical { chemical: String },
+    Cycle { chemical: String },
+    Overflow,
+}
+
+impl fmt::Display for ReactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReactionError::MalformedLine { line_number, line } => {
+                write!(f, "line {} is not a reaction: {:?}", line_number, line)
+            }
+            ReactionError::DuplicateRecipe { chemical } => {
+                write!(f, "{} is made by more than one reaction", chemical)
+            }
+            ReactionError::RecipeForOre { line_number } => {
+                write!(
+                    f,
+                    "line {} makes {}, which can only be mined",
+                    line_number, ORE
+                )
+            }
+            ReactionError::UnknownChemical { chemical } => {
+                write!(f, "no reaction makes {}", chemical)
+            }
+            ReactionError::Cycle { chemical } => {
+                write!(f, "{} is part of a reaction cycle", chemical)
+            }
+            ReactionError::Overflow => write!(f, "the amounts involved are too large"),
+        }
+    }
+}
+
+impl Error for ReactionError {}
+
+/// Every reaction the nanofactory knows, stored by the id of the chemical it makes.
+/// ORE is the only chemical without a reaction.
+#[derive(Debug)]
+pub struct Nanofactory {
+    names: Vec<String>,
+    ids: HashMap<String, ChemicalId>,
+    recipes: Vec<Option<Reaction>>,
+    /// Each chemical comes before every chemical that goes into making it, so working
+    /// through this order settles how much of a chemical is needed before its own
+    /// ingredients are looked at.
+    order: Vec<ChemicalId>,
+    ore: ChemicalId,
+}
+
+impl Nanofactory {
+    pub fn parse(input: &str) -> Result<Self, ReactionError> {
+        let mut names: Vec<String> = Vec::new();
+        let mut ids: HashMap<String, ChemicalId> = HashMap::new();
+        let mut recipes: Vec<Option<Reaction>> = Vec::new();
+
+        let mut intern = |name: &str, recipes: &mut Vec<Option<Reaction>>| -> ChemicalId {
+            if let Some(id) = ids.get(name) {
+                return *id;
+            }
+            let id = ChemicalId(names.len());
+            names.push(name.to_string());
+            ids.insert(name.to_string(), id);
+            recipes.push(None);
+            id
+        };
+        let ore = intern(ORE, &mut recipes);
+
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            let malformed = || ReactionError::MalformedLine {
+                line_number: index + 1,
+                line: line.to_string(),
+            };
+
+            let mut sides = line.split("=>");
+            let (inputs, output) = match (sides.next(), sides.next(), sides.next()) {
+                (Some(inputs), Some(output), None) => (inputs, output),
+                _ => return Err(malformed()),
+            };
+            let (output_quantity, output_name) = parse_term(output).ok_or_else(malformed)?;
+            let inputs = inputs
+                .split(',')
+                .map(|term| {
+                    parse_term(term)
+                        .map(|(quantity, name)| Ingredient {
+                            chemical: intern(name, &mut recipes),
+                            quantity,
+                        })
+                        .ok_or_else(malformed)
+                })
+                .collect::<Result<Vec<Ingredient>, ReactionError>>()?;
+
+            let output = Ingredient {
+                chemical: intern(output_name, &mut recipes),
+                quantity: output_quantity,
+            };
+            if output.chemical == ore {
+                return Err(ReactionError::RecipeForOre {
+                    line_number: index + 1,
+                });
+            }
+            if recipes[output.chemical.0].is_some() {
+                return Err(ReactionError::DuplicateRecipe {
+                    chemical: output_name.to_string(),
+                });
+            }
+            recipes[output.chemical.0] = Some(Reaction { inputs, output });
+        }
+
+        let mut factory = Nanofactory {
+            names,
+            ids,
+            recipes,
+            order: Vec::new(),
+            ore,
+        };
+        factory.order = factory.find_order()?;
+
+        Ok(factory)
+    }
+
+    /// Orders chemicals so each comes before its ingredients, by repeatedly taking a
+    /// chemical that nothing left in the list still consumes.
+    fn find_order(&self) -> Result<Vec<ChemicalId>, ReactionError> {
+        let mut consumers = vec![0; self.names.len()];
+        for (index, recipe) in self.recipes.iter().enumerate() {
+            match recipe {
+                Some(reaction) => {
+                    for input in reaction.inputs.iter() {
+                        consumers[input.chemical.0] += 1;
+                    }
+                }
+                None if index != self.ore.0 => {
+                    return Err(ReactionError::UnknownChemical {
+                        chemical: self.names[index].clone(),
+                    })
+                }
+                None => {}
+            }
+        }
+
+        let mut unconsumed: VecDeque<ChemicalId> = (0..self.names.len())
+            .filter(|&index| consumers[index] == 0)
+            .map(ChemicalId)
+            .collect();
+        let mut order = Vec::with_capacity(self.names.len());
+        while let Some(chemical) = unconsumed.pop_front() {
+            order.push(chemical);
+            for input in self.ingredients(chemical) {
+                consumers[input.chemical.0] -= 1;
+                if consumers[input.chemical.0] == 0 {
+                    unconsumed.push_back(input.chemical);
+                }
+            }
+        }
+
+        if order.len() < self.names.len() {
+            // whatever still has consumers is on a cycle or goes into one
+            let chemical = (0..self.names.len())
+                .filter(|&index| consumers[index] > 0)
+                .map(|index| &self.names[index])
+                .min()
+                .unwrap();
+            return Err(ReactionError::Cycle {
+                chemical: chemical.clone(),
+            });
+        }
+
+        Ok(order)
+    }
+
+    pub fn id(&self, name: &str) -> Result<ChemicalId, ReactionError> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| ReactionError::UnknownChemical {
+                chemical: name.to_string(),
+            })
+    }
+
+    pub fn name(&self, id: ChemicalId) -> &str {
+        &self.names[id.0]
+    }
+
+    pub fn ore(&self) -> ChemicalId {
+        self.ore
+    }
+
+    /// The reaction that makes a chemical, or `None` for ORE.
+    pub fn recipe(&self, id: ChemicalId) -> Option<&Reaction> {
+        self.recipes[id.0].as_ref()
+    }
+
+    fn ingredients(&self, id: ChemicalId) -> &[Ingredient] {
+        self.recipe(id)
+            .map_or(&[], |reaction| reaction.inputs.as_slice())
+    }
+
+    /// How much of every chemical is needed to make `amount` of one of them, and how
+    /// many times each reaction runs. Reactions only run whole numbers of times, so a
+    /// reaction can make more than is needed; the rest is left over.
+    fn requirements(
+        &self,
+        chemical: ChemicalId,
+        amount: u64,
+    ) -> Result<(Vec<u64>, Vec<u64>), ReactionError> {
+        let mut needed = vec![0; self.names.len()];
+        let mut runs = vec![0; self.names.len()];
+        needed[chemical.0] = amount;
+
+        for &current in self.order.iter() {
+            let reaction = match self.recipe(current) {
+                Some(reaction) => reaction,
+                None => continue,
+            };
+            let times = needed[current.0].div_ceil(reaction.output.quantity);
+            runs[current.0] = times;
+            for input in reaction.inputs.iter() {
+                needed[input.chemical.0] = input
+                    .quantity
+                    .checked_mul(times)
+                    .and_then(|used| used.checked_add(needed[input.chemical.0]))
+                    .ok_or(ReactionError::Overflow)?;
+            }
+        }
+
+        Ok((needed, runs))
+    }
+
+    pub fn ore_for(&self, chemical: &str, amount: u64) -> Result<u64, ReactionError> {
+        let (needed, _) = self.requirements(self.id(chemical)?, amount)?;
+        Ok(needed[self.ore.0])
+    }
+
+    pub fn ore_for_fuel(&self, fuel: u64) -> Result<u64, ReactionError> {
+        self.ore_for(FUEL, fuel)
+    }
+
+    /// A breakdown of every chemical involved in making `amount` of one, ingredients
+    /// before the things made from them.
+    pub fn explain(&self, chemical: &str, amount: u64) -> Result<Explanation, ReactionError> {
+        let (needed, runs) = self.requirements(self.id(chemical)?, amount)?;
+        let chemicals = self
+            .order
+            .iter()
+            .rev()
+            .filter(|id| needed[id.0] > 0)
+            .map(|&id| {
+                let produced = match self.recipe(id) {
+                    Some(reaction) => reaction.output.quantity * runs[id.0],
+                    None => needed[id.0],
+                };
+                ChemicalUsage {
+                    chemical: self.name(id).to_string(),
+                    runs: runs[id.0],
+                    produced,
+                    consumed: needed[id.0],
+                    leftover: produced - needed[id.0],
+                }
+            })
+            .collect();
+
+        Ok(Explanation { chemicals })
+    }
+
+    /// The recipes as a Graphviz digraph. Each edge points from an ingredient to what it
+    /// makes and is labelled with how much one run uses; each made chemical is labelled
+    /// with how much one run makes.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph reactions {\n");
+        for &chemical in self.order.iter().rev() {
+            if let Some(reaction) = self.recipe(chemical) {
+                writeln!(
+                    dot,
+                    "  {:?} [label={:?}];",
+                    self.name(chemical),
+                    format!("{} {}", reaction.output.quantity, self.name(chemical))
+                )
+                .unwrap();
+                for input in reaction.inputs.iter() {
+                    writeln!(
+                        dot,
+                        "  {:?} -> {:?} [label=\"{}\"];",
+                        self.name(input.chemical),
+                        self.name(chemical),
+                        input.quantity
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The most FUEL that can be made from `ore_budget` ORE. Spare chemicals from one
+    /// unit go towards the next, so this can beat dividing by the ORE for one FUEL.
+    pub fn max_fuel(&self, ore_budget: u64) -> Result<u64, ReactionError> {
+        let fits = |fuel: u64| -> Result<bool, ReactionError> {
+            match self.ore_for_fuel(fuel) {
+                Ok(ore) => Ok(ore <= ore_budget),
+                Err(ReactionError::Overflow) => Ok(false),
+                Err(error) => Err(error),
+            }
+        };
+
+        // grow an upper bound until it no longer fits, then narrow down between the two
+        let mut low = 0;
+        let mut high = 1;
+        while fits(high)? {
+            low = high;
+            high = match high.checked_mul(2) {
+                Some(doubled) => doubled,
+                None => return Ok(low),
+            };
+        }
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if fits(middle)? {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        Ok(low)
+    }
+}
+
+/// What happened to one chemical while making something.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChemicalUsage {
+    pub chemical: String,
+    /// How many times the reaction making it ran. Always zero for ORE, which is mined.
+    pub runs: u64,
+    pub produced: u64,
+    /// How much went into other reactions, or was asked for.
+    pub consumed: u64,
+    pub leftover: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub chemicals: Vec<ChemicalUsage>,
+}
+
+impl Explanation {
+    pub fn ore(&self) -> u64 {
+        self.chemicals
+            .iter()
+            .find(|usage| usage.chemical == ORE)
+            .map_or(0, |usage| usage.produced)
+    }
+}
+
+impl fmt::Display for Explanation {
+    /// One row per chemical, in columns wide enough for the largest value.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name_width = self
+            .chemicals
+            .iter()
+            .map(|usage| usage.chemical.len())
+            .chain(std::iter::once("chemical".len()))
+            .max()
+            .unwrap();
+        let number_width = self
+            .chemicals
+            .iter()
+            .map(|usage| usage.produced.to_string().len())
+            .chain(std::iter::once("produced".len()))
+            .max()
+            .unwrap();
+
+        writeln!(
+            f,
+            "{:<name$}  {:>num$}  {:>num$}  {:>num$}  {:>num$}",
+            "chemical",
+            "runs",
+            "produced",
+            "consumed",
+            "leftover",
+            name = name_width,
+            num = number_width
+        )?;
+        for usage in self.chemicals.iter() {
+            writeln!(
+                f,
+                "{:<name$}  {:>num$}  {:>num$}  {:>num$}  {:>num$}",
+                usage.chemical,
+                usage.runs,
+                usage.produced,
+                usage.consumed,
+                usage.leftover,
+                name = name_width,
+                num = number_width
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A quantity and chemical name, like `7 A`. A reaction can't use or make none of
+/// something, so zero quantities are rejected.
+fn parse_term(term: &str) -> Option<(u64, &str)> {
+    let mut parts = term.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(quantity), Some(name), None) => match quantity.parse::<u64>() {
+            Ok(quantity) if quantity > 0 => Some((quantity, name)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[aoc_generator(day14)]
+pub fn get_reactions(input: &str) -> Result<Nanofactory, ReactionError> {
+    Nanofactory::parse(input)
+}
+
+#[aoc(day14, part1)]
+pub fn part1(factory: &Nanofactory) -> Result<u64, ReactionError> {
+    factory.ore_for_fuel(1)
+}
+
+#[aoc(day14, part2)]
+pub fn part2(factory: &Nanofactory) -> Result<u64, ReactionError> {
+    factory.max_fuel(CARGO_HOLD_ORE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn example_1() -> Nanofactory {
+        get_reactions(indoc!(
+            "10 ORE => 10 A
+            1 ORE => 1 B
+            7 A, 1 B => 1 C
+            7 A, 1 C => 1 D
+            7 A, 1 D => 1 E
+            7 A, 1 E => 1 FUEL"
+        ))
+        .unwrap()
+    }
+
+    fn example_3() -> Nanofactory {
+        get_reactions(indoc!(
+            "157 ORE => 5 NZVS
+            165 ORE => 6 DCFZ
+            44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
+            12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
+            179 ORE => 7 PSHF
+            177 ORE => 5 HKGWZ
+            7 DCFZ, 7 PSHF => 2 XJWVT
+            165 ORE => 2 GPVTF
+            3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT"
+        ))
+        .unwrap()
+    }
+
+    fn example_4() -> Nanofactory {
+        get_reactions(indoc!(
+            "2 VPVL, 7 FWMGM, 2 CXFTF, 11 MNCFX => 1 STKFG
+            17 NVRVD, 3 JNWZP => 8 VPVL
+            53 STKFG, 6 MNCFX, 46 VJHF, 81 HVMC, 68 CXFTF, 25 GNMV => 1 FUEL
+            22 VJHF, 37 MNCFX => 5 FWMGM
+            139 ORE => 4 NVRVD
+            144 ORE => 7 JNWZP
+            5 MNCFX, 7 RFSQX, 2 FWMGM, 2 VPVL, 19 CXFTF => 3 HVMC
+            5 VJHF, 7 MNCFX, 9 VPVL, 37 CXFTF => 6 GNMV
+            145 ORE => 6 MNCFX
+            1 NVRVD => 8 CXFTF
+            1 VJHF, 6 MNCFX => 4 RFSQX
+            176 ORE => 6 VJHF"
+        ))
+        .unwrap()
+    }
+
+    fn example_5() -> Nanofactory {
+        get_reactions(indoc!(
+            "171 ORE => 8 CNZTR
+            7 ZLQW, 3 BMBT, 9 XCVML, 26 XMNCP, 1 WPTQ, 2 MZWV, 1 RJRHP => 4 PLWSL
+            114 ORE => 4 BHXH
+            14 VRPVC => 6 BMBT
+            6 BHXH, 18 KTJDG, 12 WPTQ, 7 PLWSL, 31 FHTLT, 37 ZDVW => 1 FUEL
+            6 WPTQ, 2 BMBT, 8 ZLQW, 18 KTJDG, 1 XMNCP, 6 MZWV, 1 RJRHP => 6 FHTLT
+            15 XDBXC, 2 LTCX, 1 VRPVC => 6 ZLQW
+            13 WPTQ, 10 LTCX, 3 RJRHP, 14 XMNCP, 2 MZWV, 1 ZLQW => 1 ZDVW
+            5 BMBT => 4 WPTQ
+            189 ORE => 9 KTJDG
+            1 MZWV, 17 XDBXC, 3 XCVML => 2 XMNCP
+            12 VRPVC, 27 CNZTR => 2 XDBXC
+            15 KTJDG, 12 BHXH => 5 XCVML
+            3 BHXH, 2 VRPVC => 7 MZWV
+            121 ORE => 7 VRPVC
+            7 XCVML => 6 RJRHP
+            5 BHXH, 4 VRPVC => 5 LTCX"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn ore_for_one_fuel() {
+        assert_eq!(part1(&example_1()), Ok(31));
+        assert_eq!(part1(&example_2()), Ok(165));
+        assert_eq!(part1(&example_3()), Ok(13312));
+        assert_eq!(part1(&example_4()), Ok(180697));
+        assert_eq!(part1(&example_5()), Ok(2210736));
+    }
+
+    #[test]
+    fn ore_for_other_chemicals() {
+        let factory = example_1();
+        assert_eq!(factory.ore_for("A", 11), Ok(20));
+        assert_eq!(factory.ore_for("C", 1), Ok(11));
+        assert_eq!(factory.ore_for("ORE", 5), Ok(5));
+        assert_eq!(factory.ore_for_fuel(0), Ok(0));
+        assert_eq!(
+            factory.ore_for("Z", 1),
+            Err(ReactionError::UnknownChemical {
+                chemical: "Z".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn max_fuel() {
+        assert_eq!(part2(&example_3()), Ok(82892753));
+        assert_eq!(part2(&example_4()), Ok(5586022));
+        assert_eq!(part2(&example_5()), Ok(460664));
+
+        let factory = example_1();
+        assert_eq!(factory.max_fuel(30), Ok(0));
+        assert_eq!(factory.max_fuel(31), Ok(1));
+        assert_eq!(factory.max_fuel(u64::MAX), Ok(u64::MAX / 29));
+    }
+
+    #[test]
+    fn overflow() {
+        let factory = example_1();
+        assert_eq!(
+            factory.ore_for_fuel(u64::MAX / 2),
+            Err(ReactionError::Overflow)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            get_reactions("1 ORE => 1 A\n1 A, 2 B => 1 FUEL").unwrap_err(),
+            ReactionError::UnknownChemical {
+                chemical: "B".to_string()
+            }
+        );
+        assert_eq!(
+            get_reactions("1 ORE, 1 B => 1 A\n1 A => 1 B\n1 A => 1 FUEL").unwrap_err(),
+            ReactionError::Cycle {
+                chemical: "A".to_string()
+            }
+        );
+        assert_eq!(
+            get_reactions("1 ORE => 1 A\n2 ORE => 1 A").unwrap_err(),
+            ReactionError::DuplicateRecipe {
+                chemical: "A".to_string()
+            }
+        );
+        assert_eq!(
+            get_reactions("1 A => 2 ORE").unwrap_err(),
+            ReactionError::RecipeForOre { line_number: 1 }
+        );
+        assert_eq!(
+            get_reactions("1 ORE => 1 A\n1 ORE -> 1 FUEL").unwrap_err(),
+            ReactionError::MalformedLine {
+                line_number: 2,
+                line: "1 ORE -> 1 FUEL".to_string()
+            }
+        );
+        assert!(matches!(
+            get_reactions("0 ORE => 1 A"),
+            Err(ReactionError::MalformedLine { .. })
+        ));
+    }
+
+    fn example_2() -> Nanofactory {
+        get_reactions(indoc!(
+            "9 ORE => 2 A
+            8 ORE => 3 B
+            7 ORE => 5 C
+            3 A, 4 B => 1 AB
+            5 B, 7 C => 1 BC
+            4 C, 1 A => 1 CA
+            2 AB, 3 BC, 4 CA => 1 FUEL"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn explain() {
+        let explanation = example_2().explain("FUEL", 1).unwrap();
+        let usage = |name: &str| {
+            explanation
+                .chemicals
+                .iter()
+                .find(|usage| usage.chemical == name)
+                .unwrap()
+                .clone()
+        };
+
+        assert_eq!(explanation.ore(), 165);
+        assert_eq!(
+            usage("B"),
+            ChemicalUsage {
+                chemical: "B".to_string(),
+                runs: 8,
+                produced: 24,
+                consumed: 23,
+                leftover: 1
+            }
+        );
+        assert_eq!((usage("A").runs, usage("A").leftover), (5, 0));
+        assert_eq!((usage("C").runs, usage("C").leftover), (8, 3));
+        assert_eq!(usage("FUEL").consumed, 1);
+        assert_eq!(
+            explanation.to_string(),
+            indoc!(
+                "chemical      runs  produced  consumed  leftover
+                ORE              0       165       165         0
+                A                5        10        10         0
+                C                8        40        37         3
+                B                8        24        23         1
+                CA               4         4         4         0
+                BC               3         3         3         0
+                AB               2         2         2         0
+                FUEL             1         1         1         0
+                "
+            )
+        );
+    }
+
+    #[test]
+    fn explanations_match_totals() {
+        for (factory, ore) in &[(example_3(), 13312), (example_4(), 180697)] {
+            let explanation = factory.explain("FUEL", 1).unwrap();
+            assert_eq!(explanation.ore(), *ore);
+            // everything made is either used or left over
+            for usage in explanation.chemicals.iter() {
+                assert_eq!(usage.produced, usage.consumed + usage.leftover);
+            }
+        }
+    }
+
+    #[test]
+    fn to_dot() {
+        let factory = get_reactions(indoc!(
+            "10 ORE => 10 A
+            1 ORE => 1 B
+            7 A, 1 B => 1 FUEL"
+        ))
+        .unwrap();
+        assert_eq!(
+            factory.to_dot(),
+            indoc!(
+                r#"digraph reactions {
+                  "B" [label="1 B"];
+                  "ORE" -> "B" [label="1"];
+                  "A" [label="10 A"];
+                  "ORE" -> "A" [label="10"];
+                  "FUEL" [label="1 FUEL"];
+                  "A" -> "FUEL" [label="7"];
+                  "B" -> "FUEL" [label="1"];
+                }
+                "#
+            )
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use crate::grid::{breadth_first, render_area, Area, Direction, Point, SearchTree, YAxis};
+use crate::intcode_computer::{IntcodeComputer, ProgramOutput};
+use aoc_runner_derive::{aoc, aoc_generator};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+
+/// North is up the screen, so y grows to the south.
+const Y_AXIS: YAxis = YAxis::Down;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tile {
+    Wall,
+    Open,
+    OxygenSystem,
+}
+
+impl Tile {
+    fn to_char(self) -> char {
+        match self {
+            Tile::Wall => '#',
+            Tile::Open => '.',
+            Tile::OxygenSystem => 'O',
+        }
+    }
+}
+
+impl From<i64> for Tile {
+    fn from(status: i64) -> Self {
+        match status {
+            0 => Tile::Wall,
+            1 => Tile::Open,
+            2 => Tile::OxygenSystem,
+            _ => panic!("Bad status code"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MapError {
+    UnknownTile {
+        line_number: usize,
+        column: usize,
+        found: char,
+    },
+    MissingStart,
+    MultipleStarts,
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::UnknownTile {
+                line_number,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {} column {} is not a map tile: {:?}",
+                line_number, column, found
+            ),
+            MapError::MissingStart => write!(f, "the map has no start marked"),
+            MapError::MultipleStarts => write!(f, "the map has more than one start"),
+        }
+    }
+}
+
+impl Error for MapError {}
+
+/// Something that can be steered around the ship one step at a time, reporting what it
+/// bumped into or moved onto.
+pub trait RepairDroid {
+    /// Tries to move one step. The droid stays put if the way is blocked by a wall.
+    fn try_move(&mut self, direction: Direction) -> Tile;
+}
+
+/// The real droid, driven by its remote control program.
+pub struct IntcodeDroid {
+    computer: IntcodeComputer,
+}
+
+impl IntcodeDroid {
+    pub fn new(program: &[i64]) -> Self {
+        IntcodeDroid {
+            computer: IntcodeComputer::yielding_computer(program),
+        }
+    }
+}
+
+fn movement_command(direction: Direction) -> i64 {
+    match direction {
+        Direction::Up => 1,
+        Direction::Down => 2,
+        Direction::Left => 3,
+        Direction::Right => 4,
+    }
+}
+
+impl RepairDroid for IntcodeDroid {
+    fn try_move(&mut self, direction: Direction) -> Tile {
+        self.computer.add_input(movement_command(direction));
+        match self.computer.run_program() {
+            ProgramOutput::Yielded(status) => Tile::from(status),
+            ProgramOutput::NeedsInput => panic!("Droid replied without a status"),
+            ProgramOutput::Complete(_) => panic!("Droid program stopped"),
+        }
+    }
+}
+
+/// Everything the droid has found, relative to where it started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShipMap {
+    tiles: HashMap<Point, Tile>,
+    start: Point,
+}
+
+impl ShipMap {
+    /// Maps every reachable tile with a depth first search, walking the droid back the
+    /// way it came whenever it runs out of new tiles to try.
+    pub fn explore<D: RepairDroid>(droid: &mut D) -> Self {
+        let start = Point::origin();
+        let mut tiles = HashMap::new();
+        tiles.insert(start, Tile::Open);
+
+        let mut position = start;
+        let mut route: Vec<Direction> = Vec::new();
+        loop {
+            let unexplored = Direction::all()
+                .iter()
+                .copied()
+                .find(|direction| !tiles.contains_key(&(position + direction.step(Y_AXIS))));
+            match unexplored {
+                Some(direction) => {
+                    let target = position + direction.step(Y_AXIS);
+                    let tile = droid.try_move(direction);
+                    tiles.insert(target, tile);
+                    if tile != Tile::Wall {
+                        position = target;
+                        route.push(direction);
+                    }
+                }
+                None => match route.pop() {
+                    Some(direction) => {
+                        droid.try_move(direction.reverse());
+                        position = position + direction.reverse().step(Y_AXIS);
+                    }
+                    None => break,
+                },
+            }
+        }
+
+        ShipMap { tiles, start }
+    }
+
+    /// Reads a map saved by `to_text`: `#` for walls, `.` for open floor, `O` for the
+    /// oxygen system, `S` for the open tile the droid started on and spaces for anything
+    /// never explored. Points are moved so that the start is the origin again.
+    pub fn parse(input: &str) -> Result<Self, MapError> {
+        let mut tiles = HashMap::new();
+        let mut start = None;
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let point = Point {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                let tile = match c {
+                    ' ' => continue,
+                    '#' => Tile::Wall,
+                    '.' => Tile::Open,
+                    'O' => Tile::OxygenSystem,
+                    'S' => {
+                        if start.replace(point).is_some() {
+                            return Err(MapError::MultipleStarts);
+                        }
+                        Tile::Open
+                    }
+                    found => {
+                        return Err(MapError::UnknownTile {
+                            line_number: y + 1,
+                            column: x + 1,
+                            found,
+                        })
+                    }
+                };
+                tiles.insert(point, tile);
+            }
+        }
+
+        let start = start.ok_or(MapError::MissingStart)?;
+        let tiles = tiles
+            .into_iter()
+            .map(|(point, tile)| {
+                let shifted = Point {
+                    x: point.x - start.x,
+                    y: point.y - start.y,
+                };
+                (shifted, tile)
+            })
+            .collect();
+
+        Ok(ShipMap {
+            tiles,
+            start: Point::origin(),
+        })
+    }
+
+    /// The map in the format read by `parse`.
+    pub fn to_text(&self) -> String {
+        self.render_path(&[])
+    }
+
+    /// Draws the map with `*` over the open floor along the path, leaving the start and
+    /// the oxygen system visible at either end of it.
+    pub fn render_path(&self, path: &[Point]) -> String {
+        let mut area = Area::around(&self.start);
+        for point in self.tiles.keys() {
+            area.expand_to(point);
+        }
+        let path: HashSet<&Point> = path.iter().collect();
+
+        let rendered = render_area(&area, |point| {
+            if *point == self.start {
+                return 'S';
+            }
+            match self.tiles.get(point) {
+                Some(Tile::Open) if path.contains(point) => '*',
+                Some(tile) => tile.to_char(),
+                None => ' ',
+            }
+        });
+        rendered
+            .lines()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect()
+    }
+
+    pub fn tiles(&self) -> &HashMap<Point, Tile> {
+        &self.tiles
+    }
+
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    pub fn oxygen_system(&self) -> Option<Point> {
+        self.tiles
+            .iter()
+            .find(|&(_, &tile)| tile == Tile::OxygenSystem)
+            .map(|(&point, _)| point)
+    }
+
+    fn search(&self, from: Point) -> SearchTree {
+        breadth_first(from, |point| match self.tiles.get(point) {
+            Some(Tile::Open) | Some(Tile::OxygenSystem) => true,
+            Some(Tile::Wall) | None => false,
+        })
+    }
+
+    /// The number of steps from `from` to every open tile that can be reached.
+    pub fn distances_from(&self, from: Point) -> HashMap<Point, usize> {
+        self.search(from).distances().collect()
+    }
+
+    /// The points along a shortest route, including both ends.
+    pub fn shortest_path(&self, from: Point, to: Point) -> Option<Vec<Point>> {
+        self.search(from).path_to(&to)
+    }
+
+    /// How long oxygen takes to spread from the oxygen system to every open tile, if
+    /// it moves one tile a minute.
+    pub fn minutes_to_fill(&self) -> Option<usize> {
+        self.distances_from(self.oxygen_system()?)
+            .values()
+            .max()
+            .copied()
+    }
+}
+
+#[aoc_generator(day15)]
+pub fn explore_ship(input: &str) -> Result<ShipMap, ParseIntError> {
+    let program = input
+        .split(',')
+        .map(|l| l.parse::<i64>())
+        .collect::<Result<Vec<i64>, ParseIntError>>()?;
+    Ok(ShipMap::explore(&mut IntcodeDroid::new(&program)))
+}
+
+#[aoc(day15, part1)]
+pub fn part1(map: &ShipMap) -> Option<usize> {
+    let path = map.shortest_path(map.start(), map.oxygen_system()?)?;
+    Some(path.len() - 1)
+}
+
+#[aoc(day15, part2)]
+pub fn part2(map: &ShipMap) -> Option<usize> {
+    map.minutes_to_fill()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use std::collections::HashSet;
+
+    /// A droid in a ship drawn with `#` walls, `.` floor, `O` for the oxygen system and
+    /// `D` where the droid starts.
+    struct MapDroid {
+        walls: HashSet<Point>,
+        oxygen_system: Point,
+        position: Point,
+    }
+
+    impl MapDroid {
+        fn new(picture: &str) -> Self {
+            let mut droid = MapDroid {
+                walls: HashSet::new(),
+                oxygen_system: Point::origin(),
+                position: Point::origin(),
+            };
+            let mut start = Point::origin();
+            for (y, row) in picture.lines().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    let point = Point {
+                        x: x as i32,
+                        y: y as i32,
+                    };
+                    match c {
+                        '#' => {
+                            droid.walls.insert(point);
+                        }
+                        'O' => droid.oxygen_system = point,
+                        'D' => start = point,
+                        _ => {}
+                    }
+                }
+            }
+
+            // the droid thinks of its starting point as the origin
+            let shift = |point: &Point| Point {
+                x: point.x - start.x,
+                y: point.y - start.y,
+            };
+            droid.walls = droid.walls.iter().map(shift).collect();
+            droid.oxygen_system = shift(&droid.oxygen_system);
+            droid
+        }
+    }
+
+    impl RepairDroid for MapDroid {
+        fn try_move(&mut self, direction: Direction) -> Tile {
+            let target = self.position + direction.step(Y_AXIS);
+            if self.walls.contains(&target) {
+                return Tile::Wall;
+            }
+            self.position = target;
+            if target == self.oxygen_system {
+                Tile::OxygenSystem
+            } else {
+                Tile::Open
+            }
+        }
+    }
+
+    fn example() -> MapDroid {
+        MapDroid::new(indoc!(
+            "
+             ##
+            #..##
+            #D#..#
+            #.O.#
+             ###"
+        ))
+    }
+
+    #[test]
+    fn explores_everything() {
+        let mut droid = example();
+        let map = ShipMap::explore(&mut droid);
+
+        let open = map
+            .tiles()
+            .values()
+            .filter(|&&tile| tile != Tile::Wall)
+            .count();
+        assert_eq!(open, 8);
+        assert_eq!(map.oxygen_system(), Some(Point { x: 1, y: 1 }));
+        assert_eq!(droid.position, map.start());
+    }
+
+    #[test]
+    fn shortest_path() {
+        let map = ShipMap::explore(&mut example());
+        assert_eq!(part1(&map), Some(2));
+        assert_eq!(
+            map.shortest_path(map.start(), Point { x: 2, y: 0 }),
+            Some(vec![
+                Point { x: 0, y: 0 },
+                Point { x: 0, y: 1 },
+                Point { x: 1, y: 1 },
+                Point { x: 2, y: 1 },
+                Point { x: 2, y: 0 },
+            ])
+        );
+        assert_eq!(map.shortest_path(map.start(), Point { x: 9, y: 9 }), None);
+    }
+
+    #[test]
+    fn oxygen_fill() {
+        let map = ShipMap::explore(&mut example());
+        assert_eq!(part2(&map), Some(4));
+        assert_eq!(map.distances_from(map.start())[&Point { x: 3, y: 0 }], 5);
+    }
+
+    const EXAMPLE_TEXT: &str = indoc!(
+        "
+         ##
+        #..##
+        #S#..#
+        #.O.#
+         ###
+        "
+    );
+
+    #[test]
+    fn saves_explored_map() {
+        let map = ShipMap::explore(&mut example());
+        assert_eq!(map.to_text(), EXAMPLE_TEXT);
+        assert_eq!(ShipMap::parse(&map.to_text()), Ok(map));
+    }
+
+    #[test]
+    fn renders_path() {
+        let map = ShipMap::parse(EXAMPLE_TEXT).unwrap();
+        let path = map
+            .shortest_path(map.start(), Point { x: 2, y: 0 })
+            .unwrap();
+        assert_eq!(
+            map.render_path(&path),
+            indoc!(
+                "
+                 ##
+                #..##
+                #S#*.#
+                #*O*#
+                 ###
+                "
+            )
+        );
+    }
+
+    #[test]
+    fn analyses_saved_map() {
+        let map = ShipMap::parse(EXAMPLE_TEXT).unwrap();
+        assert_eq!(map.start(), Point::origin());
+        assert_eq!(map.oxygen_system(), Some(Point { x: 1, y: 1 }));
+        assert_eq!(part1(&map), Some(2));
+        assert_eq!(part2(&map), Some(4));
+    }
+
+    #[test]
+    fn rejects_bad_maps() {
+        assert_eq!(
+            ShipMap::parse("#S#\n#x#\n"),
+            Err(MapError::UnknownTile {
+                line_number: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+        assert_eq!(ShipMap::parse("#.#\n"), Err(MapError::MissingStart));
+        assert_eq!(ShipMap::parse("S.S\n"), Err(MapError::MultipleStarts));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::digits::{parse_digits, NotADigit};
+use aoc_runner_derive::{aoc, aoc_generator};
+use std::error::Error;
+use std::fmt;
+
+const BASE_PATTERN: [i32; 4] = [0, 1, 0, -1];
+const PHASES: usize = 100;
+const REAL_SIGNAL_REPEATS: usize = 10_000;
+const OFFSET_DIGITS: usize = 7;
+const MESSAGE_LENGTH: usize = 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FftError {
+    EmptySignal,
+    NotADigit {
+        index: usize,
+        found: char,
+    },
+    /// The suffix sum shortcut only works when every digit of the message is in the
+    /// second half of the signal, where the pattern is all zeros then all ones.
+    OffsetInFirstHalf {
+        offset: usize,
+        length: usize,
+    },
+    OffsetOutOfRange {
+        offset: usize,
+        length: usize,
+    },
+}
+
+impl fmt::Display for FftError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FftError::EmptySignal => write!(f, "the signal has no digits"),
+            FftError::NotADigit { index, found } => {
+                write!(
+                    f,
+                    "expected a digit at index {} but found {:?}",
+                    index, found
+                )
+            }
+            FftError::OffsetInFirstHalf { offset, length } => write!(
+                f,
+                "message offset {} is in the first half of a {} digit signal, \
+                 so the fast decoder can't be used",
+                offset, length
+            ),
+            FftError::OffsetOutOfRange { offset, length } => write!(
+                f,
+                "a message at offset {} runs past the end of a {} digit signal",
+                offset, length
+            ),
+        }
+    }
+}
+
+impl Error for FftError {}
+
+impl From<NotADigit> for FftError {
+    fn from(NotADigit { index, found }: NotADigit) -> Self {
+        FftError::NotADigit { index, found }
+    }
+}
+
+#[aoc_generator(day16)]
+pub fn parse_signal(input: &str) -> Result<Vec<u8>, FftError> {
+    let digits = parse_digits(input)?;
+    if digits.is_empty() {
+        return Err(FftError::EmptySignal);
+    }
+    Ok(digits)
+}
+
+/// The multipliers used to calculate the output digit at `position`: each value of the
+/// base pattern repeated `position + 1` times, cycled forever, with the very first
+/// value skipped.
+pub fn pattern(position: usize) -> impl Iterator<Item = i32> {
+    BASE_PATTERN
+        .iter()
+        .flat_map(move |&value| std::iter::repeat_n(value, position + 1))
+        .cycle()
+        .skip(1)
+}
+
+pub fn phase(signal: &[u8]) -> Vec<u8> {
+    (0..signal.len())
+        .map(|position| {
+            let total: i32 = signal
+                .iter()
+                .zip(pattern(position))
+                .map(|(&digit, multiplier)| digit as i32 * multiplier)
+                .sum();
+            (total.abs() % 10) as u8
+        })
+        .collect()
+}
+
+pub fn fft(signal: &[u8], phases: usize) -> Vec<u8> {
+    (0..phases).fold(signal.to_vec(), |signal, _| phase(&signal))
+}
+
+/// Runs one phase over the end of a signal, in place. Past the halfway point each
+/// output digit is just the sum of itself and every digit after it, so this gives the
+/// same digits as `phase` as long as `tail` starts in the second half.
+pub fn suffix_sum_phase(tail: &mut [u8]) {
+    let mut sum = 0;
+    for digit in tail.iter_mut().rev() {
+        sum += *digit;
+        if sum >= 10 {
+            sum -= 10;
+        }
+        *digit = sum;
+    }
+}
+
+fn to_number(digits: &[u8]) -> usize {
+    digits
+        .iter()
+        .fold(0, |number, &digit| number * 10 + digit as usize)
+}
+
+fn to_message(digits: &[u8]) -> String {
+    digits
+        .iter()
+        .map(|&digit| char::from(b'0' + digit))
+        .collect()
+}
+
+/// Finds the message hidden in the real signal, which is `signal` repeated `repeats`
+/// times. The first seven digits give its offset; only the digits from there to the
+/// end of the real signal are ever built.
+pub fn decode_message(signal: &[u8], repeats: usize, phases: usize) -> Result<String, FftError> {
+    let length = signal.len() * repeats;
+    let offset = to_number(&signal[..OFFSET_DIGITS.min(signal.len())]);
+    if offset < length / 2 {
+        return Err(FftError::OffsetInFirstHalf { offset, length });
+    }
+    if offset + MESSAGE_LENGTH > length {
+        return Err(FftError::OffsetOutOfRange { offset, length });
+    }
+
+    let mut tail: Vec<u8> = (offset..length)
+        .map(|index| signal[index % signal.len()])
+        .collect();
+    for _ in 0..phases {
+        suffix_sum_phase(&mut tail);
+    }
+
+    Ok(to_message(&tail[..MESSAGE_LENGTH]))
+}
+
+#[aoc(day16, part1)]
+pub fn part1(signal: &[u8]) -> String {
+    let output = fft(signal, PHASES);
+    to_message(&output[..MESSAGE_LENGTH.min(output.len())])
+}
+
+#[aoc(day16, part2)]
+pub fn part2(signal: &[u8]) -> Result<String, FftError> {
+    decode_message(signal, REAL_SIGNAL_REPEATS, PHASES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(digits: &str) -> Vec<u8> {
+        parse_signal(digits).unwrap()
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(
+            pattern(0).take(8).collect::<Vec<i32>>(),
+            vec![1, 0, -1, 0, 1, 0, -1, 0]
+        );
+        assert_eq!(
+            pattern(2).take(12).collect::<Vec<i32>>(),
+            vec![0, 0, 1, 1, 1, 0, 0, 0, -1, -1, -1, 0]
+        );
+    }
+
+    #[test]
+    fn phases() {
+        let mut current = signal("12345678");
+        for expected in &["48226158", "34040438", "03415518", "01029498"] {
+            current = phase(&current);
+            assert_eq!(to_message(&current), *expected);
+        }
+    }
+
+    #[test]
+    fn part1_examples() {
+        assert_eq!(
+            part1(&signal("80871224585914546619083218645595")),
+            "24176176"
+        );
+        assert_eq!(
+            part1(&signal("19617804207202209144916044189917")),
+            "73745418"
+        );
+        assert_eq!(
+            part1(&signal("69317163492948606335995924319873")),
+            "52432133"
+        );
+    }
+
+    #[test]
+    fn suffix_sums_match_full_phases() {
+        let full = signal("69317163492948606335995924319873");
+        let mut tail = full[16..].to_vec();
+        for _ in 0..4 {
+            suffix_sum_phase(&mut tail);
+        }
+        assert_eq!(tail, fft(&full, 4)[16..].to_vec());
+    }
+
+    #[test]
+    fn part2_examples() {
+        assert_eq!(
+            part2(&signal("03036732577212944063491565474664")),
+            Ok("84462026".to_string())
+        );
+        assert_eq!(
+            part2(&signal("02935109699940807407585447034323")),
+            Ok("78725270".to_string())
+        );
+        assert_eq!(
+            part2(&signal("03081770884921959731165446850517")),
+            Ok("53553731".to_string())
+        );
+    }
+
+    #[test]
+    fn bad_offsets() {
+        assert_eq!(
+            part2(&signal("00000011234567890")),
+            Err(FftError::OffsetInFirstHalf {
+                offset: 1,
+                length: 170_000
+            })
+        );
+        assert_eq!(
+            decode_message(&signal("0000009123"), 1, 1),
+            Err(FftError::OffsetOutOfRange {
+                offset: 9,
+                length: 10
+            })
+        );
+    }
+
+    #[test]
+    fn bad_signals() {
+        assert_eq!(parse_signal("\n"), Err(FftError::EmptySignal));
+        assert_eq!(
+            parse_signal("12a4"),
+            Err(FftError::NotADigit {
+                index: 2,
+                found: 'a'
+            })
+        );
+    }
+}
@@ -0,0 +1,581 @@
+use crate::grid::{Direction, Grid, Point, Turn, Turtle, YAxis};
+use crate::intcode_computer::{IntcodeComputer, ProgramOutput};
+use aoc_runner_derive::{aoc, aoc_generator};
+use itertools::Itertools;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+
+/// The camera draws row by row from the top, so y grows downwards.
+const Y_AXIS: YAxis = YAxis::Down;
+/// The longest line, not counting the newline, the robot accepts.
+const MAX_LINE_LENGTH: usize = 20;
+const FUNCTION_NAMES: [char; 3] = ['A', 'B', 'C'];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Cell {
+    Open,
+    Scaffold,
+    Robot(Direction),
+    /// The robot, tumbling through space after falling off the scaffold.
+    LostRobot,
+}
+
+impl Cell {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Cell::Open),
+            '#' => Some(Cell::Scaffold),
+            '^' => Some(Cell::Robot(Direction::Up)),
+            'v' => Some(Cell::Robot(Direction::Down)),
+            '<' => Some(Cell::Robot(Direction::Left)),
+            '>' => Some(Cell::Robot(Direction::Right)),
+            'X' => Some(Cell::LostRobot),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Cell::Open => '.',
+            Cell::Scaffold => '#',
+            Cell::Robot(Direction::Up) => '^',
+            Cell::Robot(Direction::Down) => 'v',
+            Cell::Robot(Direction::Left) => '<',
+            Cell::Robot(Direction::Right) => '>',
+            Cell::LostRobot => 'X',
+        }
+    }
+
+    /// The robot only ever stands on scaffold, so its cell counts as scaffold too.
+    fn is_scaffold(self) -> bool {
+        match self {
+            Cell::Scaffold | Cell::Robot(_) => true,
+            Cell::Open | Cell::LostRobot => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScaffoldError {
+    UnknownCell {
+        line_number: usize,
+        column: usize,
+        found: char,
+    },
+    UnevenRows {
+        line_number: usize,
+    },
+    NotAscii(i64),
+    WaitingForInput,
+    MissingRobot,
+    EndlessRoute,
+    NoCompression,
+    NoDustReport,
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScaffoldError::UnknownCell {
+                line_number,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {} column {} is not a camera cell: {:?}",
+                line_number, column, found
+            ),
+            ScaffoldError::UnevenRows { line_number } => {
+                write!(f, "line {} is a different width to the first", line_number)
+            }
+            ScaffoldError::NotAscii(value) => {
+                write!(f, "the camera sent {}, which isn't ASCII", value)
+            }
+            ScaffoldError::WaitingForInput => {
+                write!(f, "the program is still waiting for input")
+            }
+            ScaffoldError::MissingRobot => write!(f, "the robot isn't on the scaffold"),
+            ScaffoldError::EndlessRoute => {
+                write!(f, "the scaffold loops round, so the route never ends")
+            }
+            ScaffoldError::NoCompression => write!(
+                f,
+                "the route can't be split into three movement functions that fit"
+            ),
+            ScaffoldError::NoDustReport => {
+                write!(f, "the robot stopped without reporting the dust collected")
+            }
+        }
+    }
+}
+
+impl Error for ScaffoldError {}
+
+/// What the ASCII camera can see.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaffoldView {
+    grid: Grid<Cell>,
+}
+
+impl ScaffoldView {
+    /// Blank lines at the end, which the camera sends after the picture, are ignored.
+    pub fn parse(input: &str) -> Result<Self, ScaffoldError> {
+        let rows: Vec<&str> = input.trim_end().lines().collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        let mut grid = Grid::new(width, rows.len(), Cell::Open);
+
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(ScaffoldError::UnevenRows { line_number: y + 1 });
+            }
+            for (x, c) in row.chars().enumerate() {
+                let cell = Cell::from_char(c).ok_or(ScaffoldError::UnknownCell {
+                    line_number: y + 1,
+                    column: x + 1,
+                    found: c,
+                })?;
+                let point = Point {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                *grid.get_mut(&point).unwrap() = cell;
+            }
+        }
+
+        Ok(ScaffoldView { grid })
+    }
+
+    /// Runs the camera program and reads the picture it sends back.
+    pub fn from_camera(program: &[i64]) -> Result<Self, ScaffoldError> {
+        let picture = from_ascii(&run_to_completion(program, &[])?)?;
+        ScaffoldView::parse(&picture)
+    }
+
+    pub fn grid(&self) -> &Grid<Cell> {
+        &self.grid
+    }
+
+    pub fn is_scaffold(&self, point: &Point) -> bool {
+        self.grid.get(point).is_some_and(|cell| cell.is_scaffold())
+    }
+
+    pub fn robot(&self) -> Option<(Point, Direction)> {
+        self.grid.iter().find_map(|(point, cell)| match cell {
+            Cell::Robot(heading) => Some((point, *heading)),
+            _ => None,
+        })
+    }
+
+    /// Scaffold points with scaffold on all four sides.
+    pub fn intersections(&self) -> Vec<Point> {
+        self.grid
+            .points()
+            .filter(|point| {
+                self.is_scaffold(point)
+                    && Direction::all()
+                        .iter()
+                        .all(|direction| self.is_scaffold(&(*point + direction.step(Y_AXIS))))
+            })
+            .collect()
+    }
+
+    pub fn alignment_parameters(&self) -> i32 {
+        self.intersections()
+            .iter()
+            .map(|point| point.x * point.y)
+            .sum()
+    }
+
+    /// Follows the scaffold from the robot to its far end, going straight over every
+    /// intersection, and describes the walk as turns and distances. The robot may
+    /// already be facing along the scaffold, or have to turn right round to reach it.
+    pub fn route(&self) -> Result<Vec<Move>, ScaffoldError> {
+        let (position, heading) = self.robot().ok_or(ScaffoldError::MissingRobot)?;
+        let mut turtle = Turtle::new(position, heading, Y_AXIS);
+        let mut route = Vec::new();
+        let mut turned_at = HashSet::new();
+
+        loop {
+            // the route only ever turns where it can't go straight on, so coming back
+            // to the same corner facing the same way means it's going round in circles
+            if !turned_at.insert((turtle.position, turtle.heading)) {
+                return Err(ScaffoldError::EndlessRoute);
+            }
+            // turning back only makes sense before the first step, afterwards it would
+            // just retrace the scaffold already walked
+            let choices = if route.is_empty() { 4 } else { 3 };
+            let turns = TURN_CHOICES[..choices].iter().find(|turns| {
+                let mut turned = turtle.clone();
+                turns.iter().for_each(|&turn| turned.turn(turn));
+                self.is_scaffold(&turned.ahead())
+            });
+            let turns = match turns {
+                Some(turns) => turns,
+                None => break,
+            };
+
+            for &turn in turns.iter() {
+                turtle.turn(turn);
+                route.push(Move::Turn(turn));
+            }
+            let mut distance = 0;
+            while self.is_scaffold(&turtle.ahead()) {
+                turtle.step();
+                distance += 1;
+            }
+            route.push(Move::Forward(distance));
+        }
+
+        Ok(route)
+    }
+
+    pub fn render(&self) -> String {
+        self.grid.render(|cell| cell.to_char())
+    }
+}
+
+/// The ways to face a way off the current square, in the order they're tried.
+const TURN_CHOICES: [&[Turn]; 4] = [
+    &[],
+    &[Turn::Left],
+    &[Turn::Right],
+    &[Turn::Right, Turn::Right],
+];
+
+/// One instruction for the robot: a turn on the spot or a walk forwards.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Move {
+    Turn(Turn),
+    Forward(usize),
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::Turn(Turn::Left) => write!(f, "L"),
+            Move::Turn(Turn::Right) => write!(f, "R"),
+            Move::Forward(distance) => write!(f, "{}", distance),
+        }
+    }
+}
+
+/// A route split into three movement functions and the main routine calling them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovementRoutine {
+    pub main: Vec<usize>,
+    pub functions: Vec<Vec<Move>>,
+}
+
+impl MovementRoutine {
+    /// Finds a main routine and up to three functions, each short enough for the robot,
+    /// that walk `route` exactly. Functions are tried as they're needed, so the search
+    /// backs out as soon as the route stops matching.
+    pub fn compress(route: &[Move]) -> Option<Self> {
+        let mut routine = MovementRoutine {
+            main: Vec::new(),
+            functions: Vec::new(),
+        };
+        if routine.extend(route) {
+            Some(routine)
+        } else {
+            None
+        }
+    }
+
+    fn extend(&mut self, rest: &[Move]) -> bool {
+        if rest.is_empty() {
+            return true;
+        }
+        // each extra call adds a letter and a comma
+        if self.main.len() * 2 + 1 > MAX_LINE_LENGTH {
+            return false;
+        }
+
+        for function in 0..self.functions.len() {
+            let length = self.functions[function].len();
+            if rest.starts_with(&self.functions[function]) {
+                self.main.push(function);
+                if self.extend(&rest[length..]) {
+                    return true;
+                }
+                self.main.pop();
+            }
+        }
+
+        if self.functions.len() < FUNCTION_NAMES.len() {
+            for length in 1..=rest.len() {
+                if rest[..length].iter().join(",").len() > MAX_LINE_LENGTH {
+                    break;
+                }
+                self.main.push(self.functions.len());
+                self.functions.push(rest[..length].to_vec());
+                if self.extend(&rest[length..]) {
+                    return true;
+                }
+                self.functions.pop();
+                self.main.pop();
+            }
+        }
+
+        false
+    }
+
+    /// The route the routine walks, with every call expanded.
+    pub fn expand(&self) -> Vec<Move> {
+        self.main
+            .iter()
+            .flat_map(|&function| self.functions[function].iter().copied())
+            .collect()
+    }
+
+    /// The lines typed into the robot: the main routine, each function, then whether
+    /// to show a continuous video feed.
+    pub fn to_ascii(&self, video_feed: bool) -> Vec<i64> {
+        let mut lines = vec![self
+            .main
+            .iter()
+            .map(|&function| FUNCTION_NAMES[function])
+            .join(",")];
+        for function in 0..FUNCTION_NAMES.len() {
+            lines.push(
+                self.functions
+                    .get(function)
+                    .map_or(&[][..], |f| &f[..])
+                    .iter()
+                    .join(","),
+            );
+        }
+        lines.push(if video_feed { "y" } else { "n" }.to_string());
+
+        lines
+            .iter()
+            .flat_map(|line| line.bytes().chain(std::iter::once(b'\n')))
+            .map(i64::from)
+            .collect()
+    }
+}
+
+fn run_to_completion(program: &[i64], inputs: &[i64]) -> Result<Vec<i64>, ScaffoldError> {
+    match IntcodeComputer::new(program, inputs).run_program() {
+        ProgramOutput::Complete(outputs) => Ok(outputs),
+        ProgramOutput::NeedsInput => Err(ScaffoldError::WaitingForInput),
+        ProgramOutput::Yielded(_) => unreachable!("only yielding computers yield"),
+    }
+}
+
+fn from_ascii(values: &[i64]) -> Result<String, ScaffoldError> {
+    values
+        .iter()
+        .map(|&value| match value {
+            0..=127 => Ok(value as u8 as char),
+            _ => Err(ScaffoldError::NotAscii(value)),
+        })
+        .collect()
+}
+
+/// Wakes the robot up by patching the first memory address, types in the routine and
+/// returns the amount of dust it reports, which is the one value too big for ASCII.
+pub fn collect_dust(program: &[i64], routine: &MovementRoutine) -> Result<i64, ScaffoldError> {
+    let mut program = program.to_vec();
+    program[0] = 2;
+    match run_to_completion(&program, &routine.to_ascii(false))?.last() {
+        Some(&dust) if dust > 127 => Ok(dust),
+        _ => Err(ScaffoldError::NoDustReport),
+    }
+}
+
+#[aoc_generator(day17)]
+pub fn get_program(input: &str) -> Result<Vec<i64>, ParseIntError> {
+    input.split(',').map(|l| l.parse::<i64>()).collect()
+}
+
+#[aoc(day17, part1)]
+pub fn part1(program: &[i64]) -> Result<i32, ScaffoldError> {
+    Ok(ScaffoldView::from_camera(program)?.alignment_parameters())
+}
+
+#[aoc(day17, part2)]
+pub fn part2(program: &[i64]) -> Result<i64, ScaffoldError> {
+    let route = ScaffoldView::from_camera(program)?.route()?;
+    let routine = MovementRoutine::compress(&route).ok_or(ScaffoldError::NoCompression)?;
+    collect_dust(program, &routine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const INTERSECTIONS: &str = indoc!(
+        "
+        ..#..........
+        ..#..........
+        #######...###
+        #.#...#...#.#
+        #############
+        ..#...#...#..
+        ..#####...^..
+        "
+    );
+
+    const LONG_ROUTE: &str = indoc!(
+        "
+        #######...#####
+        #.....#...#...#
+        #.....#...#...#
+        ......#...#...#
+        ......#...###.#
+        ......#.....#.#
+        ^########...#.#
+        ......#.#...#.#
+        ......#########
+        ........#...#..
+        ....#########..
+        ....#...#......
+        ....#...#......
+        ....#...#......
+        ....#####......
+        "
+    );
+
+    fn moves(text: &str) -> Vec<Move> {
+        text.split(',')
+            .map(|part| match part {
+                "L" => Move::Turn(Turn::Left),
+                "R" => Move::Turn(Turn::Right),
+                distance => Move::Forward(distance.parse().unwrap()),
+            })
+            .collect()
+    }
+
+    /// A camera program that just prints `text`.
+    fn print_program(text: &str) -> Vec<i64> {
+        text.bytes()
+            .flat_map(|byte| vec![104, byte as i64])
+            .chain(std::iter::once(99))
+            .collect()
+    }
+
+    #[test]
+    fn finds_intersections() {
+        let view = ScaffoldView::parse(INTERSECTIONS).unwrap();
+        assert_eq!(
+            view.intersections(),
+            vec![
+                Point { x: 2, y: 2 },
+                Point { x: 2, y: 4 },
+                Point { x: 6, y: 4 },
+                Point { x: 10, y: 4 }
+            ]
+        );
+        assert_eq!(view.alignment_parameters(), 76);
+        assert_eq!(view.robot(), Some((Point { x: 10, y: 6 }, Direction::Up)));
+        assert_eq!(view.render(), INTERSECTIONS);
+    }
+
+    #[test]
+    fn reads_camera() {
+        let program = print_program(&format!("{}\n", INTERSECTIONS));
+        assert_eq!(part1(&program), Ok(76));
+    }
+
+    #[test]
+    fn bad_pictures() {
+        assert_eq!(
+            ScaffoldView::parse("#.\n#?\n"),
+            Err(ScaffoldError::UnknownCell {
+                line_number: 2,
+                column: 2,
+                found: '?'
+            })
+        );
+        assert_eq!(
+            ScaffoldView::parse("#.\n#\n"),
+            Err(ScaffoldError::UnevenRows { line_number: 2 })
+        );
+        assert_eq!(
+            ScaffoldView::parse("#.\n##\n").unwrap().route(),
+            Err(ScaffoldError::MissingRobot)
+        );
+    }
+
+    #[test]
+    fn traces_route() {
+        let view = ScaffoldView::parse(LONG_ROUTE).unwrap();
+        assert_eq!(
+            view.route().unwrap(),
+            moves("R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2")
+        );
+
+        // already facing along the scaffold, and facing away from it
+        let straight = ScaffoldView::parse("..#..\n..#..\n..^##\n").unwrap();
+        assert_eq!(straight.route().unwrap(), moves("2"));
+        let backwards = ScaffoldView::parse("..#..\n..#..\n..v..\n").unwrap();
+        assert_eq!(backwards.route().unwrap(), moves("R,R,2"));
+
+        let ring = ScaffoldView::parse("###\n#.#\n#^#\n").unwrap();
+        assert_eq!(ring.route(), Err(ScaffoldError::EndlessRoute));
+    }
+
+    #[test]
+    fn compresses_route() {
+        let route = moves("R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2");
+        let routine = MovementRoutine::compress(&route).unwrap();
+        assert_eq!(routine.expand(), route);
+        assert!(routine.functions.len() <= 3);
+        for line in from_ascii(&routine.to_ascii(false)).unwrap().lines() {
+            assert!(line.len() <= MAX_LINE_LENGTH, "{} is too long", line);
+        }
+
+        // thirteen different walks, when three functions can only hold four each
+        let unrepeated: Vec<Move> = (10..23)
+            .flat_map(|distance| vec![Move::Turn(Turn::Left), Move::Forward(distance)])
+            .collect();
+        assert_eq!(MovementRoutine::compress(&unrepeated), None);
+    }
+
+    #[test]
+    fn encodes_routine() {
+        let routine = MovementRoutine {
+            main: vec![0, 1, 2, 1, 0, 2],
+            functions: vec![moves("R,8,R,8"), moves("R,4,R,4,R,8"), moves("L,6,L,2")],
+        };
+        assert_eq!(
+            from_ascii(&routine.to_ascii(false)).unwrap(),
+            "A,B,C,B,A,C\nR,8,R,8\nR,4,R,4,R,8\nL,6,L,2\nn\n"
+        );
+    }
+
+    #[test]
+    fn reports_dust() {
+        let routine = MovementRoutine {
+            main: vec![0, 0],
+            functions: vec![moves("L,4")],
+        };
+        let typed = routine.to_ascii(false).len();
+
+        // the first instruction does the same whether it adds or multiplies, so waking
+        // the robot up doesn't break it
+        let wakeable = |rest: &[i64]| [&[1, 0, 0, 1000], rest].concat();
+
+        // a prompt, a read for every character typed, then the dust total
+        let mut rest = print_program("Main:\n");
+        rest.pop();
+        for _ in 0..typed {
+            rest.extend(vec![3, 1001]);
+        }
+        rest.extend(vec![104, 12345, 99]);
+        assert_eq!(collect_dust(&wakeable(&rest), &routine), Ok(12345));
+
+        assert_eq!(
+            collect_dust(&wakeable(&print_program("..#\n")), &routine),
+            Err(ScaffoldError::NoDustReport)
+        );
+        assert_eq!(
+            collect_dust(&wakeable(&[3, 1001, 3, 1001, 99]), &routine),
+            Err(ScaffoldError::NoDustReport)
+        );
+    }
+}
@@ -0,0 +1,532 @@
+use crate::grid::{breadth_first, Grid, Point};
+use aoc_runner_derive::{aoc, aoc_generator};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VaultTile {
+    Wall,
+    Open,
+    Entrance,
+    /// A key, numbered from 0 for `a`.
+    Key(u8),
+    /// A door, numbered the same as the key that opens it.
+    Door(u8),
+}
+
+impl VaultTile {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(VaultTile::Wall),
+            '.' => Some(VaultTile::Open),
+            '@' => Some(VaultTile::Entrance),
+            'a'..='z' => Some(VaultTile::Key(c as u8 - b'a')),
+            'A'..='Z' => Some(VaultTile::Door(c as u8 - b'A')),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            VaultTile::Wall => '#',
+            VaultTile::Open => '.',
+            VaultTile::Entrance => '@',
+            VaultTile::Key(key) => char::from(b'a' + key),
+            VaultTile::Door(door) => char::from(b'A' + door),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VaultError {
+    UnknownTile {
+        line_number: usize,
+        column: usize,
+        found: char,
+    },
+    NoEntrance,
+    DuplicateKey(char),
+    /// Splitting the vault needs a single entrance with open floor all around it.
+    CantSplitEntrance,
+    KeysOutOfReach,
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VaultError::UnknownTile {
+                line_number,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {} column {} is not a vault tile: {:?}",
+                line_number, column, found
+            ),
+            VaultError::NoEntrance => write!(f, "the vault has no entrance"),
+            VaultError::DuplicateKey(key) => write!(f, "there is more than one {} key", key),
+            VaultError::CantSplitEntrance => write!(
+                f,
+                "the vault needs one entrance with open floor all around it to be split"
+            ),
+            VaultError::KeysOutOfReach => write!(f, "some keys can never be collected"),
+        }
+    }
+}
+
+impl Error for VaultError {}
+
+/// Any map of the vault. Points that aren't on the map are solid rock, the same as a
+/// wall.
+pub trait Vault {
+    fn tile(&self, point: &Point) -> VaultTile;
+    /// Every tile on the map, in no particular order.
+    fn tiles(&self) -> Vec<(Point, VaultTile)>;
+}
+
+impl Vault for Grid<VaultTile> {
+    fn tile(&self, point: &Point) -> VaultTile {
+        self.get(point).copied().unwrap_or(VaultTile::Wall)
+    }
+
+    fn tiles(&self) -> Vec<(Point, VaultTile)> {
+        self.iter().map(|(point, &tile)| (point, tile)).collect()
+    }
+}
+
+impl Vault for HashMap<Point, VaultTile> {
+    fn tile(&self, point: &Point) -> VaultTile {
+        self.get(point).copied().unwrap_or(VaultTile::Wall)
+    }
+
+    fn tiles(&self) -> Vec<(Point, VaultTile)> {
+        self.iter().map(|(&point, &tile)| (point, tile)).collect()
+    }
+}
+
+/// Lines shorter than the longest are padded out with wall.
+#[aoc_generator(day18)]
+pub fn parse_vault(input: &str) -> Result<Grid<VaultTile>, VaultError> {
+    let rows: Vec<&str> = input.lines().collect();
+    let width = rows
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut grid = Grid::new(width, rows.len(), VaultTile::Wall);
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            let tile = VaultTile::from_char(c).ok_or(VaultError::UnknownTile {
+                line_number: y + 1,
+                column: x + 1,
+                found: c,
+            })?;
+            let point = Point {
+                x: x as i32,
+                y: y as i32,
+            };
+            *grid.get_mut(&point).unwrap() = tile;
+        }
+    }
+
+    Ok(grid)
+}
+
+/// Walls off the middle of the vault so that four robots start from the corners of the
+/// old entrance, one in each quarter.
+pub fn split_entrance(vault: &Grid<VaultTile>) -> Result<Grid<VaultTile>, VaultError> {
+    let entrances: Vec<Point> = vault
+        .iter()
+        .filter(|&(_, &tile)| tile == VaultTile::Entrance)
+        .map(|(point, _)| point)
+        .collect();
+    let centre = match entrances[..] {
+        [centre] => centre,
+        _ => return Err(VaultError::CantSplitEntrance),
+    };
+
+    let mut split = vault.clone();
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let point = Point {
+                x: centre.x + dx,
+                y: centre.y + dy,
+            };
+            let tile = match split.get_mut(&point) {
+                Some(tile) => tile,
+                None => return Err(VaultError::CantSplitEntrance),
+            };
+            if *tile != VaultTile::Open && point != centre {
+                return Err(VaultError::CantSplitEntrance);
+            }
+            *tile = if dx != 0 && dy != 0 {
+                VaultTile::Entrance
+            } else {
+                VaultTile::Wall
+            };
+        }
+    }
+
+    Ok(split)
+}
+
+fn key_bit(key: u8) -> u32 {
+    1 << key
+}
+
+/// The shortest walk from one place of interest to a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub to: usize,
+    pub distance: usize,
+    /// A bit for each door in the way, set the same way as the bit for its key.
+    pub doors: u32,
+}
+
+/// Where the robots start, where the keys are and how to get between them.
+#[derive(Debug, Clone)]
+pub struct KeyGraph {
+    /// The entrances, one per robot, come first and then the keys.
+    nodes: Vec<(Point, Option<u8>)>,
+    robots: usize,
+    routes: Vec<Vec<Route>>,
+    all_keys: u32,
+}
+
+impl KeyGraph {
+    /// Measures the shortest walk from every entrance and key to every other key, noting
+    /// the doors along each walk.
+    pub fn new<V: Vault>(vault: &V) -> Result<Self, VaultError> {
+        let mut tiles = vault.tiles();
+        tiles.sort_by_key(|&(point, _)| (point.y, point.x));
+
+        let mut nodes: Vec<(Point, Option<u8>)> = tiles
+            .iter()
+            .filter(|&&(_, tile)| tile == VaultTile::Entrance)
+            .map(|&(point, _)| (point, None))
+            .collect();
+        let robots = nodes.len();
+        if robots == 0 {
+            return Err(VaultError::NoEntrance);
+        }
+
+        let mut keys: Vec<(u8, Point)> = tiles
+            .iter()
+            .filter_map(|&(point, tile)| match tile {
+                VaultTile::Key(key) => Some((key, point)),
+                _ => None,
+            })
+            .collect();
+        keys.sort_by_key(|&(key, _)| key);
+        let mut all_keys = 0;
+        for &(key, point) in &keys {
+            if all_keys & key_bit(key) != 0 {
+                return Err(VaultError::DuplicateKey(VaultTile::Key(key).to_char()));
+            }
+            all_keys |= key_bit(key);
+            nodes.push((point, Some(key)));
+        }
+
+        let routes = nodes
+            .iter()
+            .map(|&(from, _)| {
+                let tree = breadth_first(from, |point| vault.tile(point) != VaultTile::Wall);
+                nodes
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &(to, key))| key.is_some() && to != from)
+                    .filter_map(|(index, &(to, _))| {
+                        let path = tree.path_to(&to)?;
+                        let doors = path.iter().fold(0, |doors, point| match vault.tile(point) {
+                            VaultTile::Door(door) => doors | key_bit(door),
+                            _ => doors,
+                        });
+                        Some(Route {
+                            to: index,
+                            distance: path.len() - 1,
+                            doors,
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(KeyGraph {
+            nodes,
+            robots,
+            routes,
+            all_keys,
+        })
+    }
+
+    pub fn robots(&self) -> usize {
+        self.robots
+    }
+
+    /// The routes leading out from the entrance or key at `node`.
+    pub fn routes(&self, node: usize) -> &[Route] {
+        &self.routes[node]
+    }
+
+    /// Dijkstra's algorithm over states made of where every robot is and which keys
+    /// have been collected. Only one robot moves at a time, always straight to a key it
+    /// doesn't have yet through doors it has the keys for.
+    pub fn fewest_steps(&self) -> Option<usize> {
+        let start: Vec<usize> = (0..self.robots).collect();
+        let mut best: HashMap<(Vec<usize>, u32), usize> = HashMap::new();
+        best.insert((start.clone(), 0), 0);
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((0, start, 0)));
+
+        while let Some(Reverse((steps, positions, keys))) = queue.pop() {
+            if keys == self.all_keys {
+                return Some(steps);
+            }
+            if best
+                .get(&(positions.clone(), keys))
+                .is_some_and(|&known| known < steps)
+            {
+                continue;
+            }
+
+            for (robot, &node) in positions.iter().enumerate() {
+                for route in &self.routes[node] {
+                    let key = key_bit(self.nodes[route.to].1.expect("routes lead to keys"));
+                    if keys & key != 0 || route.doors & !keys != 0 {
+                        continue;
+                    }
+
+                    let mut next = positions.clone();
+                    next[robot] = route.to;
+                    let next_keys = keys | key;
+                    let next_steps = steps + route.distance;
+                    let state = (next, next_keys);
+                    if best.get(&state).is_some_and(|&known| known <= next_steps) {
+                        continue;
+                    }
+                    best.insert(state.clone(), next_steps);
+                    queue.push(Reverse((next_steps, state.0, next_keys)));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[aoc(day18, part1)]
+pub fn part1(vault: &Grid<VaultTile>) -> Result<usize, VaultError> {
+    KeyGraph::new(vault)?
+        .fewest_steps()
+        .ok_or(VaultError::KeysOutOfReach)
+}
+
+#[aoc(day18, part2)]
+pub fn part2(vault: &Grid<VaultTile>) -> Result<usize, VaultError> {
+    KeyGraph::new(&split_entrance(vault)?)?
+        .fewest_steps()
+        .ok_or(VaultError::KeysOutOfReach)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn fewest_steps(input: &str) -> Option<usize> {
+        KeyGraph::new(&parse_vault(input).unwrap())
+            .unwrap()
+            .fewest_steps()
+    }
+
+    #[test]
+    fn routes_note_doors() {
+        let graph =
+            KeyGraph::new(&parse_vault("#########\n#b.A.@.a#\n#########").unwrap()).unwrap();
+        assert_eq!(graph.robots(), 1);
+        assert_eq!(
+            graph.routes(0),
+            &[
+                Route {
+                    to: 1,
+                    distance: 2,
+                    doors: 0
+                },
+                Route {
+                    to: 2,
+                    distance: 4,
+                    doors: key_bit(0)
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn part1_examples() {
+        assert_eq!(fewest_steps("#########\n#b.A.@.a#\n#########"), Some(8));
+        assert_eq!(
+            fewest_steps(indoc!(
+                "
+                ########################
+                #f.D.E.e.C.b.A.@.a.B.c.#
+                ######################.#
+                #d.....................#
+                ########################"
+            )),
+            Some(86)
+        );
+        assert_eq!(
+            fewest_steps(indoc!(
+                "
+                ########################
+                #...............b.C.D.f#
+                #.######################
+                #.....@.a.B.c.d.A.e.F.g#
+                ########################"
+            )),
+            Some(132)
+        );
+        assert_eq!(
+            fewest_steps(indoc!(
+                "
+                #################
+                #i.G..c...e..H.p#
+                ########.########
+                #j.A..b...f..D.o#
+                ########@########
+                #k.E..a...g..B.n#
+                ########.########
+                #l.F..d...h..C.m#
+                #################"
+            )),
+            Some(136)
+        );
+        assert_eq!(
+            fewest_steps(indoc!(
+                "
+                ########################
+                #@..............ac.GI.b#
+                ###d#e#f################
+                ###A#B#C################
+                ###g#h#i################
+                ########################"
+            )),
+            Some(81)
+        );
+    }
+
+    #[test]
+    fn splits_entrance() {
+        let vault = parse_vault(indoc!(
+            "
+            #######
+            #a.#Cd#
+            ##...##
+            ##.@.##
+            ##...##
+            #cB#Ab#
+            #######"
+        ))
+        .unwrap();
+        let split = split_entrance(&vault).unwrap();
+        assert_eq!(
+            split.render(|tile| tile.to_char()),
+            indoc!(
+                "
+                #######
+                #a.#Cd#
+                ##@#@##
+                #######
+                ##@#@##
+                #cB#Ab#
+                #######
+                "
+            )
+        );
+        assert_eq!(part2(&vault), Ok(8));
+        assert_eq!(split_entrance(&split), Err(VaultError::CantSplitEntrance));
+    }
+
+    #[test]
+    fn part2_examples() {
+        assert_eq!(
+            fewest_steps(indoc!(
+                "
+                ###############
+                #d.ABC.#.....a#
+                ######@#@######
+                ###############
+                ######@#@######
+                #b.....#.....c#
+                ###############"
+            )),
+            Some(24)
+        );
+        assert_eq!(
+            fewest_steps(indoc!(
+                "
+                #############
+                #DcBa.#.GhKl#
+                #.###@#@#I###
+                #e#d#####j#k#
+                ###C#@#@###J#
+                #fEbA.#.FgHi#
+                #############"
+            )),
+            Some(32)
+        );
+        assert_eq!(
+            fewest_steps(indoc!(
+                "
+                #############
+                #g#f.D#..h#l#
+                #F###e#E###.#
+                #dCba@#@BcIJ#
+                #############
+                #nK.L@#@G...#
+                #M###N#H###.#
+                #o#m..#i#jk.#
+                #############"
+            )),
+            Some(72)
+        );
+    }
+
+    #[test]
+    fn works_on_any_map() {
+        let sparse: HashMap<Point, VaultTile> = parse_vault("#b.A.@.a#")
+            .unwrap()
+            .iter()
+            .filter(|&(_, &tile)| tile != VaultTile::Wall)
+            .map(|(point, &tile)| (point, tile))
+            .collect();
+        assert_eq!(KeyGraph::new(&sparse).unwrap().fewest_steps(), Some(8));
+    }
+
+    #[test]
+    fn bad_vaults() {
+        assert_eq!(
+            parse_vault("#@?#"),
+            Err(VaultError::UnknownTile {
+                line_number: 1,
+                column: 3,
+                found: '?'
+            })
+        );
+        assert_eq!(
+            KeyGraph::new(&parse_vault("#a.a#").unwrap()).unwrap_err(),
+            VaultError::NoEntrance
+        );
+        assert_eq!(
+            KeyGraph::new(&parse_vault("#a@a#").unwrap()).unwrap_err(),
+            VaultError::DuplicateKey('a')
+        );
+        assert_eq!(
+            part1(&parse_vault("#@#a#").unwrap()),
+            Err(VaultError::KeysOutOfReach)
+        );
+    }
+}
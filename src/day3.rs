@@ -1,30 +1,12 @@
-use crate::grid::Point;
+use crate::grid::{Area, Direction, Point, YAxis};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::HashMap;
+use itertools::Itertools;
+use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
 use std::num::ParseIntError;
 use std::str::FromStr;
 
-#[derive(PartialEq, Eq, Debug)]
-enum Direction {
-    UP,
-    DOWN,
-    LEFT,
-    RIGHT,
-}
-
-impl FromStr for Direction {
-    type Err = ();
-    fn from_str(letter: &str) -> Result<Self, Self::Err> {
-        match letter {
-            "U" => Ok(Direction::UP),
-            "D" => Ok(Direction::DOWN),
-            "L" => Ok(Direction::LEFT),
-            "R" => Ok(Direction::RIGHT),
-            _ => Err(()),
-        }
-    }
-}
-
 #[derive(Eq, PartialEq, Debug)]
 pub struct PathPart {
     direction: Direction,
@@ -32,32 +14,66 @@ pub struct PathPart {
 }
 
 pub struct WirePaths {
-    a: Vec<PathPart>,
-    b: Vec<PathPart>,
+    wires: Vec<Vec<PathPart>>,
 }
 
-#[derive(Debug)]
-struct CrossedBy<T> {
-    a: Option<T>,
-    b: Option<T>,
+/// A straight run of wire, with the number of steps the wire took to reach its start.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+struct Segment {
+    start: Point,
+    end: Point,
+    steps_before: i32,
 }
 
-impl<T> CrossedBy<T> {
-    fn new() -> Self {
-        CrossedBy {
-            a: Option::None,
-            b: Option::None,
-        }
+impl Segment {
+    fn steps_to(&self, point: &Point) -> i32 {
+        self.steps_before + (point.x - self.start.x).abs() + (point.y - self.start.y).abs()
+    }
+
+    /// Both segments are axis aligned, so where their bounding boxes overlap is exactly
+    /// where they cross - a single point, or a run of points if they lie on the same line.
+    fn crossings(&self, other: &Segment) -> Vec<Point> {
+        let min_x = cmp::max(
+            cmp::min(self.start.x, self.end.x),
+            cmp::min(other.start.x, other.end.x),
+        );
+        let max_x = cmp::min(
+            cmp::max(self.start.x, self.end.x),
+            cmp::max(other.start.x, other.end.x),
+        );
+        let min_y = cmp::max(
+            cmp::min(self.start.y, self.end.y),
+            cmp::min(other.start.y, other.end.y),
+        );
+        let max_y = cmp::min(
+            cmp::max(self.start.y, self.end.y),
+            cmp::max(other.start.y, other.end.y),
+        );
+
+        (min_x..=max_x)
+            .flat_map(|x| (min_y..=max_y).map(move |y| Point { x, y }))
+            .collect()
+    }
+}
+
+/// A point where two or more wires cross, with the fewest steps each of those wires
+/// takes to reach it, keyed by the wire's index.
+#[derive(Eq, PartialEq, Debug)]
+pub struct Intersection {
+    pub point: Point,
+    pub wire_steps: BTreeMap<usize, i32>,
+}
+
+impl Intersection {
+    pub fn total_steps(&self) -> i32 {
+        self.wire_steps.values().sum()
     }
 }
 
 #[aoc_generator(day3)]
 pub fn get_wire_paths(input: &str) -> Result<WirePaths, ParseIntError> {
-    let mut lines = input.lines();
-
     Ok(WirePaths {
-        a: parse_line(lines.next().unwrap()),
-        b: parse_line(lines.next().unwrap()),
+        wires: input.lines().map(parse_line).collect(),
     })
 }
 
@@ -75,124 +91,180 @@ fn parse_line(line: &str) -> Vec<PathPart> {
 
 #[aoc(day3, part1)]
 pub fn part1(wire_paths: &WirePaths) -> i32 {
-    let mut grid: HashMap<Point, CrossedBy<()>> = HashMap::new();
-    let mut intersections: Vec<Point> = Vec::new();
-
-    for (index, path) in (vec![&wire_paths.a, &wire_paths.b]).iter().enumerate() {
-        traverse_wire_path(path, &Point::origin(), |_, &current_point| {
-            let cross = grid.entry(current_point).or_insert(CrossedBy::new());
-            if index == 0 && cross.a.is_none() {
-                cross.a = Option::Some(());
-            } else if index == 1 && cross.b.is_none() {
-                cross.b = Option::Some(());
-            }
-
-            if cross.a.is_some() && cross.b.is_some() {
-                intersections.push(current_point.clone());
-            }
-        });
-    }
+    let intersections: Vec<Point> = find_intersections(&wire_paths.wires)
+        .iter()
+        .map(|intersection| intersection.point)
+        .collect();
 
     find_closest_intersection_distance(&intersections)
 }
 
 #[aoc(day3, part2)]
 pub fn part2(wire_paths: &WirePaths) -> i32 {
-    let mut grid: HashMap<Point, CrossedBy<i32>> = HashMap::new();
-    let mut intersection_total_steps: Vec<i32> = Vec::new();
-
-    for (index, path) in (vec![&wire_paths.a, &wire_paths.b]).iter().enumerate() {
-        traverse_wire_path(path, &Point::origin(), |step_number, &current_point| {
-            let cross = grid.entry(current_point).or_insert(CrossedBy::new());
-            if index == 0 && cross.a.is_none() {
-                cross.a = Option::Some(step_number);
-            } else if index == 1 && cross.b.is_none() {
-                cross.b = Option::Some(step_number);
-            }
+    find_intersections(&wire_paths.wires)
+        .iter()
+        .map(Intersection::total_steps)
+        .min()
+        .unwrap_or(i32::MAX)
+}
 
-            if cross.a.is_some() && cross.b.is_some() {
-                intersection_total_steps.push(cross.a.unwrap() + cross.b.unwrap());
-            }
+fn to_segments(wire_path: &[PathPart], start_point: &Point) -> Vec<Segment> {
+    let mut segments = Vec::with_capacity(wire_path.len());
+    let mut current = *start_point;
+    let mut steps_before = 0;
+
+    for part in wire_path {
+        let step = part.direction.step(YAxis::Up);
+        let end = Point {
+            x: current.x + step.x * part.distance,
+            y: current.y + step.y * part.distance,
+        };
+        segments.push(Segment {
+            start: current,
+            end,
+            steps_before,
         });
+        current = end;
+        steps_before += part.distance;
     }
 
-    intersection_total_steps
-        .iter()
-        .fold(std::i32::MAX, |x, y| std::cmp::min(x, *y))
+    segments
 }
 
-/// This will not runn the callback for the start_point - we don't count this as a "crossed" point
-fn traverse_wire_path<F>(wire_path: &Vec<PathPart>, start_point: &Point, mut point_callback: F)
-where
-    F: FnMut(i32, &Point) -> (),
-{
-    let mut last_point = start_point.clone();
-    let mut step_number = 0;
-    for part in wire_path {
-        let new_point = match part.direction {
-            Direction::UP => {
-                for y_val in last_point.y + 1..=last_point.y + part.distance {
-                    let current_point = Point {
-                        x: last_point.x,
-                        y: y_val,
-                    };
-                    step_number = step_number + 1;
-                    point_callback(step_number, &current_point);
-                }
-
-                Point {
-                    x: last_point.x,
-                    y: last_point.y + part.distance,
+/// Every point, other than the origin they all start from, crossed by more than one
+/// wire. Sorted closest to the origin first.
+pub fn find_intersections(wires: &[Vec<PathPart>]) -> Vec<Intersection> {
+    let origin = Point::origin();
+    let segments: Vec<Vec<Segment>> = wires
+        .iter()
+        .map(|wire| to_segments(wire, &origin))
+        .collect();
+
+    let mut crossings: HashMap<Point, BTreeMap<usize, i32>> = HashMap::new();
+    for (a_index, b_index) in (0..segments.len()).tuple_combinations() {
+        for a in segments[a_index].iter() {
+            for b in segments[b_index].iter() {
+                for point in a.crossings(b) {
+                    if point == origin {
+                        continue;
+                    }
+                    let wire_steps = crossings.entry(point).or_default();
+                    for (index, segment) in [(a_index, a), (b_index, b)].iter() {
+                        let steps = segment.steps_to(&point);
+                        let fewest = wire_steps.entry(*index).or_insert(steps);
+                        *fewest = cmp::min(*fewest, steps);
+                    }
                 }
             }
-            Direction::DOWN => {
-                for y_val in (last_point.y - part.distance..=last_point.y - 1).rev() {
-                    let current_point = Point {
-                        x: last_point.x,
-                        y: y_val,
-                    };
-                    step_number = step_number + 1;
-                    point_callback(step_number, &current_point);
-                }
+        }
+    }
 
-                Point {
-                    x: last_point.x,
-                    y: last_point.y - part.distance,
-                }
-            }
-            Direction::LEFT => {
-                for x_val in (last_point.x - part.distance..=last_point.x - 1).rev() {
-                    let current_point = Point {
-                        x: x_val,
-                        y: last_point.y,
-                    };
-                    step_number = step_number + 1;
-                    point_callback(step_number, &current_point);
-                }
+    let mut intersections: Vec<Intersection> = crossings
+        .into_iter()
+        .map(|(point, wire_steps)| Intersection { point, wire_steps })
+        .collect();
+    intersections.sort_by_key(|intersection| {
+        let point = intersection.point;
+        (point.x.abs() + point.y.abs(), point.x, point.y)
+    });
 
-                Point {
-                    x: last_point.x - part.distance,
-                    y: last_point.y,
-                }
-            }
-            Direction::RIGHT => {
-                for x_val in last_point.x + 1..=last_point.x + part.distance {
-                    let current_point = Point {
-                        x: x_val,
-                        y: last_point.y,
-                    };
-                    step_number = step_number + 1;
-                    point_callback(step_number, &current_point);
-                }
+    intersections
+}
 
-                Point {
-                    x: last_point.x + part.distance,
-                    y: last_point.y,
-                }
-            }
-        };
+const WIRE_COLORS: [&str; 5] = ["#d62728", "#1f77b4", "#2ca02c", "#9467bd", "#ff7f0e"];
+
+impl WirePaths {
+    /// Draws each wire as a polyline, with the origin, every intersection, the crossing
+    /// closest to the origin and the crossing with the fewest combined steps marked.
+    /// Up in the puzzle is up in the image.
+    pub fn to_svg(&self) -> String {
+        let origin = Point::origin();
+        let vertices: Vec<Vec<Point>> = self
+            .wires
+            .iter()
+            .map(|wire| {
+                let segments = to_segments(wire, &origin);
+                std::iter::once(origin)
+                    .chain(segments.iter().map(|segment| segment.end))
+                    .collect()
+            })
+            .collect();
+        let area = Area::from_point_list(&vertices.iter().flatten().collect());
+
+        let size = cmp::max(area.max_x - area.min_x, area.max_y - area.min_y) + 1;
+        let margin = size / 20 + 1;
+        let marker_radius = size / 100 + 1;
+        // svg y increases downwards, so flip it around the top of the area
+        let to_svg_point = |point: &Point| (point.x, area.max_y - point.y);
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            area.min_x - margin,
+            -margin,
+            area.max_x - area.min_x + 2 * margin,
+            area.max_y - area.min_y + 2 * margin
+        )
+        .unwrap();
+
+        for (index, wire) in vertices.iter().enumerate() {
+            let points = wire
+                .iter()
+                .map(|point| {
+                    let (x, y) = to_svg_point(point);
+                    format!("{},{}", x, y)
+                })
+                .join(" ");
+            writeln!(
+                svg,
+                r#"  <polyline points="{}" fill="none" stroke="{}" stroke-width="1" vector-effect="non-scaling-stroke"/>"#,
+                points,
+                WIRE_COLORS[index % WIRE_COLORS.len()]
+            )
+            .unwrap();
+        }
+
+        const ORIGIN: &str = r#"fill="black""#;
+        const INTERSECTION: &str = r#"fill="gray""#;
+        const CLOSEST: &str = r#"fill="none" stroke="black" vector-effect="non-scaling-stroke""#;
+        const FEWEST_STEPS: &str =
+            r#"fill="none" stroke="green" vector-effect="non-scaling-stroke""#;
+
+        let intersections = find_intersections(&self.wires);
+        let mut markers = vec![("origin", origin, marker_radius, ORIGIN)];
+        for intersection in intersections.iter() {
+            markers.push((
+                "intersection",
+                intersection.point,
+                marker_radius,
+                INTERSECTION,
+            ));
+        }
+        if let Some(closest) = intersections.first() {
+            markers.push(("closest", closest.point, marker_radius * 3, CLOSEST));
+        }
+        if let Some(fewest_steps) = intersections.iter().min_by_key(|i| i.total_steps()) {
+            markers.push((
+                "fewest-steps",
+                fewest_steps.point,
+                marker_radius * 4,
+                FEWEST_STEPS,
+            ));
+        }
 
-        last_point = new_point.clone();
+        for (name, point, radius, style) in markers {
+            let (x, y) = to_svg_point(&point);
+            writeln!(
+                svg,
+                r#"  <circle class="{}" cx="{}" cy="{}" r="{}" {}><title>{} ({}, {})</title></circle>"#,
+                name, x, y, radius, style, name, point.x, point.y
+            )
+            .unwrap();
+        }
+
+        svg.push_str("</svg>\n");
+        svg
     }
 }
 
@@ -220,23 +292,23 @@ mod tests {
             parse_line(&"R83,U83,L12,D49,R71"),
             vec![
                 PathPart {
-                    direction: Direction::RIGHT,
+                    direction: Direction::Right,
                     distance: 83
                 },
                 PathPart {
-                    direction: Direction::UP,
+                    direction: Direction::Up,
                     distance: 83
                 },
                 PathPart {
-                    direction: Direction::LEFT,
+                    direction: Direction::Left,
                     distance: 12
                 },
                 PathPart {
-                    direction: Direction::DOWN,
+                    direction: Direction::Down,
                     distance: 49
                 },
                 PathPart {
-                    direction: Direction::RIGHT,
+                    direction: Direction::Right,
                     distance: 71
                 },
             ]
@@ -273,8 +345,10 @@ mod tests {
     fn part1_example1() {
         assert_eq!(
             part1(&WirePaths {
-                a: parse_line(&"R75,D30,R83,U83,L12,D49,R71,U7,L72"),
-                b: parse_line(&"U62,R66,U55,R34,D71,R55,D58,R83"),
+                wires: vec![
+                    parse_line(&"R75,D30,R83,U83,L12,D49,R71,U7,L72"),
+                    parse_line(&"U62,R66,U55,R34,D71,R55,D58,R83"),
+                ],
             }),
             159
         );
@@ -284,8 +358,10 @@ mod tests {
     fn part1_example2() {
         assert_eq!(
             part1(&WirePaths {
-                a: parse_line(&"R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51"),
-                b: parse_line(&"U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"),
+                wires: vec![
+                    parse_line(&"R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51"),
+                    parse_line(&"U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"),
+                ],
             }),
             135
         );
@@ -295,8 +371,10 @@ mod tests {
     fn part2_example1() {
         assert_eq!(
             part2(&WirePaths {
-                a: parse_line(&"R75,D30,R83,U83,L12,D49,R71,U7,L72"),
-                b: parse_line(&"U62,R66,U55,R34,D71,R55,D58,R83"),
+                wires: vec![
+                    parse_line(&"R75,D30,R83,U83,L12,D49,R71,U7,L72"),
+                    parse_line(&"U62,R66,U55,R34,D71,R55,D58,R83"),
+                ],
             }),
             610
         );
@@ -306,10 +384,105 @@ mod tests {
     fn part2_example2() {
         assert_eq!(
             part2(&WirePaths {
-                a: parse_line(&"R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51"),
-                b: parse_line(&"U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"),
+                wires: vec![
+                    parse_line(&"R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51"),
+                    parse_line(&"U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"),
+                ],
             }),
             410
         );
     }
+
+    #[test]
+    fn intersections_with_steps() {
+        assert_eq!(
+            find_intersections(&[parse_line(&"R8,U5,L5,D3"), parse_line(&"U7,R6,D4,L4")]),
+            vec![
+                Intersection {
+                    point: Point { x: 3, y: 3 },
+                    wire_steps: vec![(0, 20), (1, 20)].into_iter().collect(),
+                },
+                Intersection {
+                    point: Point { x: 6, y: 5 },
+                    wire_steps: vec![(0, 15), (1, 15)].into_iter().collect(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_wires() {
+        let intersections = find_intersections(&[parse_line(&"R5"), parse_line(&"U1,R2,D1,R4")]);
+        assert_eq!(
+            intersections
+                .iter()
+                .map(|intersection| intersection.point)
+                .collect::<Vec<Point>>(),
+            vec![
+                Point { x: 2, y: 0 },
+                Point { x: 3, y: 0 },
+                Point { x: 4, y: 0 },
+                Point { x: 5, y: 0 },
+            ]
+        );
+        assert_eq!(intersections[3].total_steps(), 5 + 7);
+    }
+
+    #[test]
+    fn more_than_two_wires() {
+        let intersections = find_intersections(&[
+            parse_line(&"R8,U5,L5,D3"),
+            parse_line(&"U7,R6,D4,L4"),
+            parse_line(&"D1,R4,U10"),
+        ]);
+        assert_eq!(
+            intersections,
+            vec![
+                Intersection {
+                    point: Point { x: 4, y: 0 },
+                    wire_steps: vec![(0, 4), (2, 6)].into_iter().collect(),
+                },
+                Intersection {
+                    point: Point { x: 3, y: 3 },
+                    wire_steps: vec![(0, 20), (1, 20)].into_iter().collect(),
+                },
+                Intersection {
+                    point: Point { x: 4, y: 3 },
+                    wire_steps: vec![(1, 19), (2, 9)].into_iter().collect(),
+                },
+                Intersection {
+                    point: Point { x: 4, y: 5 },
+                    wire_steps: vec![(0, 17), (2, 11)].into_iter().collect(),
+                },
+                Intersection {
+                    point: Point { x: 4, y: 7 },
+                    wire_steps: vec![(1, 11), (2, 13)].into_iter().collect(),
+                },
+                Intersection {
+                    point: Point { x: 6, y: 5 },
+                    wire_steps: vec![(0, 15), (1, 15)].into_iter().collect(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn svg_export() {
+        let svg = WirePaths {
+            wires: vec![parse_line(&"R8,U5,L5,D3"), parse_line(&"U7,R6,D4,L4")],
+        }
+        .to_svg();
+        let lines: Vec<&str> = svg.lines().collect();
+
+        assert_eq!(lines.len(), 9);
+        assert!(lines[0].contains(r#"viewBox="-1 -1 10 9""#));
+        assert!(lines[1].contains(r#"points="0,7 8,7 8,2 3,2 3,5""#));
+        assert!(lines[2].contains(r#"points="0,7 0,0 6,0 6,4 2,4""#));
+        assert!(lines[3].starts_with(r#"  <circle class="origin" cx="0" cy="7""#));
+        assert!(lines[4].starts_with(r#"  <circle class="intersection" cx="3" cy="4""#));
+        assert!(lines[5].starts_with(r#"  <circle class="intersection" cx="6" cy="2""#));
+        assert!(lines[6].starts_with(r#"  <circle class="closest" cx="3" cy="4""#));
+        assert!(lines[7].starts_with(r#"  <circle class="fewest-steps" cx="6" cy="2""#));
+        assert_eq!(lines[8], "</svg>");
+    }
 }
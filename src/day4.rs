@@ -1,6 +1,7 @@
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 
 #[derive(Debug)]
 pub struct IntRange {
@@ -21,29 +22,358 @@ pub fn parse_data(input: &str) -> IntRange {
 }
 
 #[aoc(day4, part1)]
-pub fn part1(range: &IntRange) -> u32 {
-    let mut counter = 0;
-    for number in range.bottom..=range.top {
-        if is_valid_solution_for_part1(number) {
-            counter = counter + 1;
+pub fn part1(range: &IntRange) -> u64 {
+    count_non_decreasing(u64::from(range.bottom), u64::from(range.top), has_pair)
+}
+
+#[aoc(day4, part2)]
+pub fn part2(range: &IntRange) -> u64 {
+    count_non_decreasing(
+        u64::from(range.bottom),
+        u64::from(range.top),
+        has_exact_pair,
+    )
+}
+
+#[aoc(day4, part1, Enumerated)]
+pub fn part1_enumerated(range: &IntRange) -> usize {
+    passwords(u64::from(range.bottom), u64::from(range.top), part1_rule()).count()
+}
+
+#[aoc(day4, part2, Enumerated)]
+pub fn part2_enumerated(range: &IntRange) -> usize {
+    passwords(u64::from(range.bottom), u64::from(range.top), part2_rule()).count()
+}
+
+/// Part 1's rule: some digit appears at least twice in a row.
+pub fn has_pair(run_length: usize) -> bool {
+    run_length >= 2
+}
+
+/// Part 2's rule: some digit appears exactly twice in a row.
+pub fn has_exact_pair(run_length: usize) -> bool {
+    run_length == 2
+}
+
+/// Counts the numbers in `bottom..=top` whose digits never decrease and which have at
+/// least one run of equal digits whose length passes `run_rule`. The count is built up
+/// a digit at a time, so the size of the range doesn't matter.
+pub fn count_non_decreasing<F>(bottom: u64, top: u64, run_rule: F) -> u64
+where
+    F: Fn(usize) -> bool,
+{
+    if bottom > top {
+        return 0;
+    }
+    let below_bottom = match bottom {
+        0 => 0,
+        _ => count_up_to(bottom - 1, &run_rule),
+    };
+
+    count_up_to(top, &run_rule) - below_bottom
+}
+
+fn count_up_to<F>(limit: u64, run_rule: &F) -> u64
+where
+    F: Fn(usize) -> bool,
+{
+    let mut counter = DigitCounter {
+        digits: limit.to_string().bytes().map(|byte| byte - b'0').collect(),
+        run_rule,
+        memo: HashMap::new(),
+    };
+    let start = DigitState {
+        previous: None,
+        run_length: 0,
+        satisfied: false,
+    };
+
+    counter.count(0, start, true)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct DigitState {
+    /// The last digit placed, or `None` while there have only been leading zeros.
+    previous: Option<u8>,
+    run_length: usize,
+    /// Whether a run that has already ended passed the rule.
+    satisfied: bool,
+}
+
+struct DigitCounter<'a, F> {
+    digits: Vec<u8>,
+    run_rule: &'a F,
+    memo: HashMap<(usize, DigitState, bool), u64>,
+}
+
+impl<'a, F> DigitCounter<'a, F>
+where
+    F: Fn(usize) -> bool,
+{
+    /// How many ways the digits from `position` onwards can be filled in. While `tight`
+    /// every digit so far has matched the limit, so the next one can't go above it.
+    fn count(&mut self, position: usize, state: DigitState, tight: bool) -> u64 {
+        if position == self.digits.len() {
+            let passes = match state.previous {
+                Some(_) => state.satisfied || (self.run_rule)(state.run_length),
+                // the number zero, a single run of one digit
+                None => (self.run_rule)(1),
+            };
+            return passes as u64;
+        }
+
+        let key = (position, state, tight);
+        if let Some(&count) = self.memo.get(&key) {
+            return count;
+        }
+
+        let max_digit = if tight { self.digits[position] } else { 9 };
+        let mut count = 0;
+        for digit in state.previous.unwrap_or(0)..=max_digit {
+            let next = match state.previous {
+                None if digit == 0 => state,
+                None => DigitState {
+                    previous: Some(digit),
+                    run_length: 1,
+                    satisfied: false,
+                },
+                Some(previous) if previous == digit => DigitState {
+                    run_length: state.run_length + 1,
+                    ..state
+                },
+                Some(_) => DigitState {
+                    previous: Some(digit),
+                    run_length: 1,
+                    satisfied: state.satisfied || (self.run_rule)(state.run_length),
+                },
+            };
+            count += self.count(position + 1, next, tight && digit == max_digit);
+        }
+
+        self.memo.insert(key, count);
+        count
+    }
+}
+
+/// A condition on the digits of a password, most significant digit first.
+pub trait Rule {
+    fn matches(&self, digits: &[u8]) -> bool;
+
+    /// The smallest number no less than `number` that could match, or `None` if no
+    /// such number fits in a u64. Rules that can rule out whole stretches of numbers at
+    /// once override this so enumeration can skip them.
+    fn next_candidate(&self, number: u64) -> Option<u64> {
+        Some(number)
+    }
+
+    fn matches_number(&self, number: u64) -> bool {
+        self.matches(&digits_of(number))
+    }
+
+    fn and<R: Rule>(self, other: R) -> And<Self, R>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    fn or<R: Rule>(self, other: R) -> Or<Self, R>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+}
+
+impl<F> Rule for F
+where
+    F: Fn(&[u8]) -> bool,
+{
+    fn matches(&self, digits: &[u8]) -> bool {
+        self(digits)
+    }
+}
+
+/// Going from left to right, the digits never decrease.
+pub struct NonDecreasing;
+
+impl Rule for NonDecreasing {
+    fn matches(&self, digits: &[u8]) -> bool {
+        digits
+            .iter()
+            .tuple_windows()
+            .all(|(left, right)| left <= right)
+    }
+
+    /// Copies the digit before the first decrease over everything after it.
+    fn next_candidate(&self, number: u64) -> Option<u64> {
+        let mut digits = digits_of(number);
+        if let Some(drop) = (1..digits.len()).find(|&i| digits[i] < digits[i - 1]) {
+            let fill = digits[drop - 1];
+            for digit in digits[drop..].iter_mut() {
+                *digit = fill;
+            }
         }
+
+        number_of(&digits)
     }
+}
 
-    counter
+/// At least two neighbouring digits are the same.
+pub struct AdjacentEqual;
+
+impl Rule for AdjacentEqual {
+    fn matches(&self, digits: &[u8]) -> bool {
+        digits
+            .iter()
+            .tuple_windows()
+            .any(|(left, right)| left == right)
+    }
 }
 
-#[aoc(day4, part2)]
-pub fn part2(range: &IntRange) -> u32 {
-    let mut counter = 0;
-    for number in range.bottom..=range.top {
-        if is_valid_solution_for_part2(number) {
-            counter = counter + 1;
+/// Some digit is repeated exactly this many times in a row, no more and no fewer.
+pub struct RunLength(pub usize);
+
+impl Rule for RunLength {
+    fn matches(&self, digits: &[u8]) -> bool {
+        digits
+            .iter()
+            .group_by(|&&digit| digit)
+            .into_iter()
+            .any(|(_, run)| run.count() == self.0)
+    }
+}
+
+/// The password has exactly this many digits.
+pub struct DigitCount(pub usize);
+
+impl Rule for DigitCount {
+    fn matches(&self, digits: &[u8]) -> bool {
+        digits.len() == self.0
+    }
+
+    fn next_candidate(&self, number: u64) -> Option<u64> {
+        match self.0 {
+            0 => Some(number),
+            length => {
+                let smallest = 10u64.checked_pow(u32::try_from(length - 1).ok()?)?;
+                Some(number.max(smallest))
+            }
         }
     }
+}
+
+pub struct And<A, B>(A, B);
+
+impl<A: Rule, B: Rule> Rule for And<A, B> {
+    fn matches(&self, digits: &[u8]) -> bool {
+        self.0.matches(digits) && self.1.matches(digits)
+    }
 
-    counter
+    /// Anything both sides would skip can be skipped, so keep asking each side in turn
+    /// until neither moves.
+    fn next_candidate(&self, number: u64) -> Option<u64> {
+        let mut candidate = number;
+        loop {
+            let next = self
+                .0
+                .next_candidate(candidate)
+                .and_then(|next| self.1.next_candidate(next))?;
+            if next == candidate {
+                return Some(candidate);
+            }
+            candidate = next;
+        }
+    }
 }
 
+pub struct Or<A, B>(A, B);
+
+impl<A: Rule, B: Rule> Rule for Or<A, B> {
+    fn matches(&self, digits: &[u8]) -> bool {
+        self.0.matches(digits) || self.1.matches(digits)
+    }
+
+    fn next_candidate(&self, number: u64) -> Option<u64> {
+        match (self.0.next_candidate(number), self.1.next_candidate(number)) {
+            (Some(left), Some(right)) => Some(left.min(right)),
+            (left, right) => left.or(right),
+        }
+    }
+}
+
+pub struct Not<A>(A);
+
+impl<A: Rule> Rule for Not<A> {
+    fn matches(&self, digits: &[u8]) -> bool {
+        !self.0.matches(digits)
+    }
+}
+
+pub fn part1_rule() -> impl Rule {
+    NonDecreasing.and(AdjacentEqual)
+}
+
+pub fn part2_rule() -> impl Rule {
+    NonDecreasing.and(RunLength(2))
+}
+
+fn digits_of(number: u64) -> Vec<u8> {
+    number.to_string().bytes().map(|byte| byte - b'0').collect()
+}
+
+/// `None` if the digits make a number too big for a u64.
+fn number_of(digits: &[u8]) -> Option<u64> {
+    digits.iter().try_fold(0u64, |number, &digit| {
+        number.checked_mul(10)?.checked_add(u64::from(digit))
+    })
+}
+
+/// Every number in `bottom..=top` that matches a rule, in increasing order.
+pub struct Passwords<R> {
+    next: Option<u64>,
+    top: u64,
+    rule: R,
+}
+
+pub fn passwords<R: Rule>(bottom: u64, top: u64, rule: R) -> Passwords<R> {
+    Passwords {
+        next: Some(bottom),
+        top,
+        rule,
+    }
+}
+
+impl<R: Rule> Iterator for Passwords<R> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            let candidate = match self.rule.next_candidate(self.next?) {
+                Some(candidate) if candidate <= self.top => candidate,
+                _ => {
+                    self.next = None;
+                    return None;
+                }
+            };
+            self.next = candidate.checked_add(1);
+            if self.rule.matches_number(candidate) {
+                return Some(candidate);
+            }
+        }
+    }
+}
+
+// The original string-based checkers, kept as an independent oracle for the digit
+// DP and the rule engine.
+#[cfg(test)]
 fn is_valid_solution_for_part1(number: u32) -> bool {
     let as_str = number.to_string();
 
@@ -65,6 +395,7 @@ fn is_valid_solution_for_part1(number: u32) -> bool {
     double_found
 }
 
+#[cfg(test)]
 fn is_valid_solution_for_part2(number: u32) -> bool {
     if !is_valid_solution_for_part1(number) {
         return false;
@@ -142,3 +473,150 @@ mod part2_tests {
         assert_eq!(is_valid_solution_for_part2(12344), true);
     }
 }
+
+#[cfg(test)]
+mod counting_tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_brute_force() {
+        let range = IntRange {
+            bottom: 100000,
+            top: 999999,
+        };
+        let brute_force = |is_valid: fn(u32) -> bool| {
+            (range.bottom..=range.top)
+                .filter(|&number| is_valid(number))
+                .count() as u64
+        };
+        assert_eq!(part1(&range), brute_force(is_valid_solution_for_part1));
+        assert_eq!(part2(&range), brute_force(is_valid_solution_for_part2));
+    }
+
+    #[test]
+    fn bounds_of_different_lengths() {
+        for &(bottom, top) in &[(0, 0), (0, 1000), (7, 12345), (99, 100), (1223, 1222)] {
+            let brute_force = |is_valid: fn(u32) -> bool| {
+                (bottom..=top).filter(|&number| is_valid(number)).count() as u64
+            };
+            assert_eq!(
+                count_non_decreasing(u64::from(bottom), u64::from(top), has_pair),
+                brute_force(is_valid_solution_for_part1)
+            );
+            assert_eq!(
+                count_non_decreasing(u64::from(bottom), u64::from(top), has_exact_pair),
+                brute_force(is_valid_solution_for_part2)
+            );
+        }
+    }
+
+    #[test]
+    fn huge_ranges() {
+        // choosing L digits from 1-9 with repetition, summed over L from 1 to 9
+        assert_eq!(count_non_decreasing(1, 999_999_999, |_| true), 48619);
+        assert_eq!(count_non_decreasing(0, 0, |_| true), 1);
+        assert!(count_non_decreasing(0, u64::MAX, has_exact_pair) > 0);
+    }
+}
+
+#[cfg(test)]
+mod rule_tests {
+    use super::*;
+
+    #[test]
+    fn single_rules() {
+        assert!(NonDecreasing.matches_number(112233));
+        assert!(!NonDecreasing.matches_number(112213));
+        assert!(AdjacentEqual.matches_number(123445));
+        assert!(!AdjacentEqual.matches_number(123454));
+        assert!(RunLength(3).matches_number(1222));
+        assert!(!RunLength(3).matches_number(12222));
+        assert!(DigitCount(6).matches_number(100000));
+        assert!(!DigitCount(6).matches_number(99999));
+    }
+
+    #[test]
+    fn combined_rules() {
+        let triple_but_no_pair = RunLength(3).and(RunLength(2).not());
+        assert!(triple_but_no_pair.matches_number(111234));
+        assert!(!triple_but_no_pair.matches_number(111223));
+
+        let ends_in_nine = |digits: &[u8]| digits.last() == Some(&9);
+        let either = RunLength(4).or(ends_in_nine);
+        assert!(either.matches_number(11119));
+        assert!(either.matches_number(11110));
+        assert!(either.matches_number(12349));
+        assert!(!either.matches_number(12340));
+    }
+
+    #[test]
+    fn skipping_to_candidates() {
+        assert_eq!(NonDecreasing.next_candidate(278384), Some(278888));
+        assert_eq!(NonDecreasing.next_candidate(123459), Some(123459));
+        assert_eq!(DigitCount(4).next_candidate(12), Some(1000));
+        assert_eq!(
+            NonDecreasing.and(DigitCount(3)).next_candidate(98),
+            Some(111)
+        );
+
+        // 18446744073709551615 would have to become 18888888888888888888
+        assert_eq!(NonDecreasing.next_candidate(u64::MAX), None);
+        assert_eq!(DigitCount(21).next_candidate(0), None);
+        assert_eq!(
+            NonDecreasing.or(DigitCount(21)).next_candidate(u64::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn enumeration() {
+        assert_eq!(
+            passwords(100, 130, part1_rule()).collect::<Vec<u64>>(),
+            vec![111, 112, 113, 114, 115, 116, 117, 118, 119, 122]
+        );
+        assert_eq!(
+            passwords(100, 999, RunLength(3)).collect::<Vec<u64>>(),
+            (1..=9).map(|digit| digit * 111).collect::<Vec<u64>>()
+        );
+        assert_eq!(
+            passwords(u64::MAX - 1, u64::MAX, |_: &[u8]| true).count(),
+            2
+        );
+        assert_eq!(passwords(u64::MAX - 5, u64::MAX, part1_rule()).count(), 0);
+    }
+
+    #[test]
+    fn rules_agree_with_checkers() {
+        for number in (0..1_000_000).step_by(7) {
+            assert_eq!(
+                part1_rule().matches_number(u64::from(number)),
+                is_valid_solution_for_part1(number),
+                "part 1 rule on {}",
+                number
+            );
+            assert_eq!(
+                part2_rule().matches_number(u64::from(number)),
+                is_valid_solution_for_part2(number),
+                "part 2 rule on {}",
+                number
+            );
+        }
+    }
+
+    #[test]
+    fn enumeration_agrees_with_counting() {
+        let range = IntRange {
+            bottom: 278384,
+            top: 824795,
+        };
+        assert_eq!(part1_enumerated(&range) as u64, part1(&range));
+        assert_eq!(part2_enumerated(&range) as u64, part2(&range));
+
+        for &rule_length in &[1, 3, 4] {
+            assert_eq!(
+                passwords(0, 99999, NonDecreasing.and(RunLength(rule_length))).count() as u64,
+                count_non_decreasing(0, 99999, |run_length| run_length == rule_length)
+            );
+        }
+    }
+}
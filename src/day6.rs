@@ -1,159 +1,447 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::HashMap;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write;
+
+const CENTER_OF_MASS: &str = "COM";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyId(usize);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OrbitError {
+    MalformedLine { line_number: usize, line: String },
+    MultipleParents { body: String, parents: [String; 2] },
+    MissingCenterOfMass,
+    CenterOfMassOrbits { parent: String },
+    Cycle { body: String },
+    NotOrbitingCenterOfMass { body: String },
+    UnknownBody { body: String },
+    OrbitsNothing { body: String },
+}
 
-#[derive(Debug)]
-pub struct OrbitPair {
-    parent: String,
-    satellite: String,
+impl fmt::Display for OrbitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrbitError::MalformedLine { line_number, line } => {
+                write!(f, "line {} is not of the form A)B: {:?}", line_number, line)
+            }
+            OrbitError::MultipleParents { body, parents } => {
+                write!(f, "{} orbits both {} and {}", body, parents[0], parents[1])
+            }
+            OrbitError::MissingCenterOfMass => write!(f, "nothing orbits {}", CENTER_OF_MASS),
+            OrbitError::CenterOfMassOrbits { parent } => {
+                write!(f, "{} orbits {}", CENTER_OF_MASS, parent)
+            }
+            OrbitError::Cycle { body } => write!(f, "{} is part of an orbit cycle", body),
+            OrbitError::NotOrbitingCenterOfMass { body } => {
+                write!(f, "{} does not lead back to {}", body, CENTER_OF_MASS)
+            }
+            OrbitError::UnknownBody { body } => write!(f, "{} is not in the orbit map", body),
+            OrbitError::OrbitsNothing { body } => write!(f, "{} does not orbit anything", body),
+        }
+    }
 }
 
+impl Error for OrbitError {}
+
+/// Every body in the map, stored by id, with COM at the root. Names are only used at
+/// the edges - parsing and looking bodies up.
 #[derive(Debug)]
-pub struct OrbitGraphEdges {
-    parent: Option<String>,
-    satellites: Vec<String>,
+pub struct OrbitMap {
+    names: Vec<String>,
+    ids: HashMap<String, BodyId>,
+    parents: Vec<Option<BodyId>>,
+    satellites: Vec<Vec<BodyId>>,
+    depths: Vec<u32>,
+    root: BodyId,
 }
 
-impl OrbitGraphEdges {
-    fn new() -> Self {
-        OrbitGraphEdges {
-            parent: None,
-            satellites: Vec::new(),
+impl OrbitMap {
+    pub fn parse(input: &str) -> Result<Self, OrbitError> {
+        let mut names: Vec<String> = Vec::new();
+        let mut ids: HashMap<String, BodyId> = HashMap::new();
+        let mut parents: Vec<Option<BodyId>> = Vec::new();
+        let mut satellites: Vec<Vec<BodyId>> = Vec::new();
+
+        let mut intern = |name: &str| -> BodyId {
+            if let Some(id) = ids.get(name) {
+                return *id;
+            }
+            let id = BodyId(names.len());
+            names.push(name.to_string());
+            ids.insert(name.to_string(), id);
+            parents.push(None);
+            satellites.push(Vec::new());
+            id
+        };
+
+        let mut pairs: Vec<(BodyId, BodyId)> = Vec::new();
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            let mut split = line.split(')');
+            match (split.next(), split.next(), split.next()) {
+                (Some(parent), Some(satellite), None)
+                    if !parent.is_empty() && !satellite.is_empty() =>
+                {
+                    pairs.push((intern(parent), intern(satellite)));
+                }
+                _ => {
+                    return Err(OrbitError::MalformedLine {
+                        line_number: index + 1,
+                        line: line.to_string(),
+                    })
+                }
+            }
         }
-    }
-}
 
-#[aoc_generator(day6)]
-pub fn get_orbits(input: &str) -> Vec<OrbitPair> {
-    input
-        .lines()
-        .map(|line| {
-            let split = line.split(')').collect::<Vec<_>>();
-            OrbitPair {
-                parent: split.get(0).unwrap().to_string(),
-                satellite: split.get(1).unwrap().to_string(),
+        for (parent, satellite) in pairs {
+            if let Some(existing) = parents[satellite.0] {
+                return Err(OrbitError::MultipleParents {
+                    body: names[satellite.0].clone(),
+                    parents: [names[existing.0].clone(), names[parent.0].clone()],
+                });
             }
+            parents[satellite.0] = Some(parent);
+            satellites[parent.0].push(satellite);
+        }
+
+        let root = *ids
+            .get(CENTER_OF_MASS)
+            .ok_or(OrbitError::MissingCenterOfMass)?;
+        // the root has to stay a root, or walking up from any body would never stop
+        if let Some(parent) = parents[root.0] {
+            return Err(OrbitMap::root_error(&names, &parents, root, parent));
+        }
+        let depths = OrbitMap::find_depths(&names, &parents, &satellites, root)?;
+
+        Ok(OrbitMap {
+            names,
+            ids,
+            parents,
+            satellites,
+            depths,
+            root,
         })
-        .collect()
-}
+    }
+
+    /// Walks down from the root. Anything it can't reach is either stuck in a cycle or
+    /// hanging off some other root.
+    /// Works out why the root orbits `parent`: either the bodies above it lead back
+    /// round to the root, or the root just orbits something else.
+    fn root_error(
+        names: &[String],
+        parents: &[Option<BodyId>],
+        root: BodyId,
+        parent: BodyId,
+    ) -> OrbitError {
+        let mut current = parent;
+        // any walk longer than the number of bodies must be going round a cycle
+        for _ in 0..names.len() {
+            if current == root {
+                return OrbitError::Cycle {
+                    body: names[root.0].clone(),
+                };
+            }
+            current = match parents[current.0] {
+                Some(next) => next,
+                None => {
+                    return OrbitError::CenterOfMassOrbits {
+                        parent: names[parent.0].clone(),
+                    }
+                }
+            };
+        }
+        OrbitError::Cycle {
+            body: names[parent.0].clone(),
+        }
+    }
+
+    fn find_depths(
+        names: &[String],
+        parents: &[Option<BodyId>],
+        satellites: &[Vec<BodyId>],
+        root: BodyId,
+    ) -> Result<Vec<u32>, OrbitError> {
+        let mut depths: Vec<Option<u32>> = vec![None; names.len()];
+        depths[root.0] = Some(0);
+
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+        while let Some(current) = queue.pop_front() {
+            for satellite in satellites[current.0].iter() {
+                if depths[satellite.0].is_none() {
+                    depths[satellite.0] = Some(depths[current.0].unwrap() + 1);
+                    queue.push_back(*satellite);
+                }
+            }
+        }
 
-fn build_graph(pairs: &[OrbitPair]) -> HashMap<String, OrbitGraphEdges> {
-    let mut orbit_graph: HashMap<String, OrbitGraphEdges> = HashMap::new();
-    for orbit_pair in pairs {
-        let parent_entry = orbit_graph
-            .entry(orbit_pair.parent.clone())
-            .or_insert(OrbitGraphEdges::new());
-        parent_entry.satellites.push(orbit_pair.satellite.clone());
+        if let Some(unreached) = depths.iter().position(Option::is_none) {
+            let mut seen = HashSet::new();
+            let mut current = BodyId(unreached);
+            while seen.insert(current) {
+                match parents[current.0] {
+                    Some(parent) => current = parent,
+                    None => {
+                        return Err(OrbitError::NotOrbitingCenterOfMass {
+                            body: names[current.0].clone(),
+                        })
+                    }
+                }
+            }
+            return Err(OrbitError::Cycle {
+                body: names[current.0].clone(),
+            });
+        }
 
-        let satellite_entry = orbit_graph
-            .entry(orbit_pair.satellite.clone())
-            .or_insert(OrbitGraphEdges::new());
-        satellite_entry.parent = Some(orbit_pair.parent.clone());
+        Ok(depths.into_iter().map(Option::unwrap).collect())
     }
 
-    orbit_graph
-}
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
 
-#[aoc(day6, part1)]
-pub fn part1(orbit_pairs: &[OrbitPair]) -> u32 {
-    let orbits = build_graph(&orbit_pairs);
-    get_total_depths(&orbits, "COM".to_string(), 0)
-}
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
 
-#[aoc(day6, part2)]
-pub fn part2(orbit_pairs: &[OrbitPair]) -> Option<u32> {
-    get_num_orbital_transfers(&orbit_pairs, "SAN", "YOU")
-}
+    pub fn root(&self) -> BodyId {
+        self.root
+    }
 
-fn get_num_orbital_transfers(orbit_pairs: &[OrbitPair], start: &str, end: &str) -> Option<u32> {
-    let start_is_orbiting = find_parent(&orbit_pairs, start).unwrap();
-    let end_is_orbiting = find_parent(&orbit_pairs, end).unwrap();
-    let orbit_graph = build_graph(&orbit_pairs);
-
-    let mut current_orbit_name = start_is_orbiting;
-    let mut result: Option<u32> = None;
-    let mut distance = 0;
-
-    while result.is_none() {
-        result = get_depth_to_target(
-            &orbit_graph,
-            &current_orbit_name,
-            distance,
-            &end_is_orbiting,
-        );
+    pub fn id(&self, name: &str) -> Result<BodyId, OrbitError> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| OrbitError::UnknownBody {
+                body: name.to_string(),
+            })
+    }
 
-        if result.is_some() {
-            break;
-        }
+    pub fn name(&self, body: BodyId) -> &str {
+        &self.names[body.0]
+    }
 
-        // covers the case where the end node is orbiting the root
-        if current_orbit_name == end_is_orbiting {
-            result = Some(distance);
-            break;
+    pub fn bodies(&self) -> impl Iterator<Item = BodyId> {
+        (0..self.names.len()).map(BodyId)
+    }
+
+    pub fn parent(&self, body: BodyId) -> Option<BodyId> {
+        self.parents[body.0]
+    }
+
+    pub fn satellites(&self, body: BodyId) -> &[BodyId] {
+        &self.satellites[body.0]
+    }
+
+    /// How many direct and indirect orbits the body has, i.e. its distance from COM.
+    pub fn depth(&self, body: BodyId) -> u32 {
+        self.depths[body.0]
+    }
+
+    /// Everything the body orbits, directly or indirectly, starting with its parent
+    /// and ending with COM.
+    pub fn ancestors(&self, body: BodyId) -> Vec<BodyId> {
+        let mut ancestors = Vec::with_capacity(self.depth(body) as usize);
+        let mut current = body;
+        while let Some(parent) = self.parent(current) {
+            ancestors.push(parent);
+            current = parent;
         }
 
-        let current_orbit = orbit_graph.get(&current_orbit_name.clone()).unwrap();
-        if current_orbit.parent.is_none() {
-            // We've hit the root of the tree without finding it
-            break;
+        ancestors
+    }
+
+    /// The deepest body that both bodies are, or orbit.
+    pub fn lowest_common_ancestor(&self, a: BodyId, b: BodyId) -> BodyId {
+        let (mut a, mut b) = (a, b);
+        while self.depth(a) > self.depth(b) {
+            a = self.parent(a).unwrap();
+        }
+        while self.depth(b) > self.depth(a) {
+            b = self.parent(b).unwrap();
+        }
+        while a != b {
+            a = self.parent(a).unwrap();
+            b = self.parent(b).unwrap();
         }
 
-        // Go up one level and continue
-        current_orbit_name = current_orbit.parent.as_ref().unwrap().clone();
-        distance += 1;
+        a
     }
 
-    result
-}
+    /// The number of orbit edges between the two bodies.
+    pub fn distance(&self, a: BodyId, b: BodyId) -> u32 {
+        let common = self.lowest_common_ancestor(a, b);
+        self.depth(a) + self.depth(b) - 2 * self.depth(common)
+    }
 
-fn find_parent(orbit_pairs: &[OrbitPair], target_name: &str) -> Option<String> {
-    orbit_pairs.iter().find_map(|orbit| {
-        if orbit.satellite == target_name {
-            return Some(orbit.parent.clone());
-        }
+    pub fn total_orbits(&self) -> u32 {
+        self.depths.iter().sum()
+    }
 
-        None
-    })
-}
+    /// Transfers needed to move from the body `start` orbits to the body `end` orbits.
+    pub fn orbital_transfers(&self, start: &str, end: &str) -> Result<u32, OrbitError> {
+        let start_parent = self.parent_of(start)?;
+        let end_parent = self.parent_of(end)?;
+        Ok(self.distance(start_parent, end_parent))
+    }
 
-fn get_total_depths(
-    orbits: &HashMap<String, OrbitGraphEdges>,
-    current_node_name: String,
-    current_depth: u32,
-) -> u32 {
-    let current_node = orbits.get(&current_node_name);
-
-    return current_depth
-        + current_node.map_or(0, |node| {
-            node.satellites.iter().fold(0, |acc, curr| {
-                acc + get_total_depths(orbits, curr.to_string(), current_depth + 1)
+    fn parent_of(&self, name: &str) -> Result<BodyId, OrbitError> {
+        self.parent(self.id(name)?)
+            .ok_or_else(|| OrbitError::OrbitsNothing {
+                body: name.to_string(),
             })
-        });
-}
+    }
 
-fn get_depth_to_target(
-    orbits: &HashMap<String, OrbitGraphEdges>,
-    current_node_name: &String,
-    current_depth: u32,
-    target_node_name: &String,
-) -> Option<u32> {
-    let current_node = orbits.get(current_node_name).unwrap();
-
-    for satellite in current_node.satellites.iter() {
-        // we want the depth of the parent, not the satellite itself
-        // but target_node_name *is* the parent, so it's the next depth
-        // in retrospect, target_node_name should probably have been the actual target
-        if satellite == target_node_name {
-            return Some(current_depth + 1);
+    /// Every body on the way from `a` to `b`, including both ends.
+    pub fn path(&self, a: BodyId, b: BodyId) -> Vec<BodyId> {
+        let common = self.lowest_common_ancestor(a, b);
+        let climb = |from: BodyId| {
+            let mut bodies = vec![from];
+            let mut current = from;
+            while current != common {
+                current = self.parent(current).unwrap();
+                bodies.push(current);
+            }
+            bodies
+        };
+
+        let mut path = climb(a);
+        let mut down = climb(b);
+        down.pop();
+        path.extend(down.into_iter().rev());
+        path
+    }
+
+    pub fn summary(&self) -> OrbitSummary {
+        let direct_orbits = self
+            .parents
+            .iter()
+            .filter(|parent| parent.is_some())
+            .count() as u32;
+        let deepest = self
+            .bodies()
+            .max_by_key(|body| (self.depth(*body), std::cmp::Reverse(*body)))
+            .unwrap_or(self.root);
+        let deepest_chain: Vec<String> = self
+            .ancestors(deepest)
+            .into_iter()
+            .rev()
+            .chain(std::iter::once(deepest))
+            .map(|body| self.name(body).to_string())
+            .collect();
+
+        OrbitSummary {
+            bodies: self.len(),
+            direct_orbits,
+            indirect_orbits: self.total_orbits() - direct_orbits,
+            max_depth: self.depth(deepest),
+            leaf_count: self.satellites.iter().filter(|s| s.is_empty()).count(),
+            deepest_chain,
         }
+    }
 
-        let result = get_depth_to_target(&orbits, satellite, current_depth + 1, target_node_name);
+    /// The whole tree as a Graphviz digraph, with edges pointing from parent to satellite.
+    pub fn to_dot(&self) -> String {
+        self.dot(&[])
+    }
 
-        if result.is_some() {
-            return result;
+    /// Like `to_dot`, with the transfer path between the two bodies highlighted.
+    pub fn to_dot_with_path(&self, a: BodyId, b: BodyId) -> String {
+        self.dot(&self.path(a, b))
+    }
+
+    fn dot(&self, highlighted: &[BodyId]) -> String {
+        const HIGHLIGHT: &str = " [color=red, penwidth=2]";
+        let path_edges: HashSet<(BodyId, BodyId)> = highlighted
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .flat_map(|(a, b)| vec![(a, b), (b, a)])
+            .collect();
+
+        let mut dot = String::from("digraph orbits {\n");
+        for body in highlighted {
+            writeln!(dot, "  {}{};", quote(self.name(*body)), HIGHLIGHT).unwrap();
+        }
+        for body in self.bodies() {
+            for satellite in self.satellites(body) {
+                writeln!(
+                    dot,
+                    "  {} -> {}{};",
+                    quote(self.name(body)),
+                    quote(self.name(*satellite)),
+                    if path_edges.contains(&(body, *satellite)) {
+                        HIGHLIGHT
+                    } else {
+                        ""
+                    }
+                )
+                .unwrap();
+            }
         }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Quotes a name for use as a Graphviz id or a JSON string.
+fn quote(name: &str) -> String {
+    let mut quoted = String::with_capacity(name.len() + 2);
+    quoted.push('"');
+    for c in name.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct OrbitSummary {
+    pub bodies: usize,
+    pub direct_orbits: u32,
+    pub indirect_orbits: u32,
+    pub max_depth: u32,
+    pub leaf_count: usize,
+    /// From COM down to the deepest body. The earliest listed body wins a tie.
+    pub deepest_chain: Vec<String>,
+}
+
+impl OrbitSummary {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"bodies\":{},\"direct_orbits\":{},\"indirect_orbits\":{},\"total_orbits\":{},\"max_depth\":{},\"leaf_count\":{},\"deepest_chain\":[{}]}}",
+            self.bodies,
+            self.direct_orbits,
+            self.indirect_orbits,
+            self.direct_orbits + self.indirect_orbits,
+            self.max_depth,
+            self.leaf_count,
+            self.deepest_chain.iter().map(|name| quote(name)).join(",")
+        )
     }
+}
+
+#[aoc_generator(day6)]
+pub fn get_orbits(input: &str) -> Result<OrbitMap, OrbitError> {
+    OrbitMap::parse(input)
+}
 
-    None
+#[aoc(day6, part1)]
+pub fn part1(orbits: &OrbitMap) -> u32 {
+    orbits.total_orbits()
+}
+
+#[aoc(day6, part2)]
+pub fn part2(orbits: &OrbitMap) -> Result<u32, OrbitError> {
+    orbits.orbital_transfers("SAN", "YOU")
 }
 
 #[cfg(test)]
@@ -163,29 +451,27 @@ mod tests {
 
     #[test]
     fn total_orbits_single_satellite() {
-        assert_eq!(part1(&get_orbits(&indoc!("COM)BFS"))), 1);
+        assert_eq!(part1(&get_orbits(&indoc!("COM)BFS")).unwrap()), 1);
     }
 
     #[test]
     fn total_orbits_two_satellites_of_parent() {
-        assert_eq!(
-            part1(&get_orbits(&indoc!(
-                "COM)BFS
-                COM)123"
-            ))),
-            2
-        );
+        let orbits = get_orbits(&indoc!(
+            "COM)BFS
+            COM)123"
+        ))
+        .unwrap();
+        assert_eq!(part1(&orbits), 2);
     }
 
     #[test]
     fn total_orbits_two_satellite_chain() {
-        assert_eq!(
-            part1(&get_orbits(&indoc!(
-                "COM)BFS
-                BFS)123"
-            ))),
-            3
-        );
+        let orbits = get_orbits(&indoc!(
+            "COM)BFS
+            BFS)123"
+        ))
+        .unwrap();
+        assert_eq!(part1(&orbits), 3);
     }
 
     #[test]
@@ -196,11 +482,9 @@ mod tests {
             START)ABC
             ABC)FOO
             FOO)END"
-        ));
-        assert_eq!(
-            get_num_orbital_transfers(&orbits, &"START", &"END"),
-            Some(3)
-        );
+        ))
+        .unwrap();
+        assert_eq!(orbits.orbital_transfers("START", "END"), Ok(3));
     }
 
     #[test]
@@ -211,11 +495,9 @@ mod tests {
             START)ABC
             ABC)FOO
             FOO)END"
-        ));
-        assert_eq!(
-            get_num_orbital_transfers(&orbits, &"END", &"START"),
-            Some(3)
-        );
+        ))
+        .unwrap();
+        assert_eq!(orbits.orbital_transfers("END", "START"), Ok(3));
     }
 
     #[test]
@@ -227,11 +509,9 @@ mod tests {
             B)C
             C)D
             D)END"
-        ));
-        assert_eq!(
-            get_num_orbital_transfers(&orbits, &"START", &"END"),
-            Some(2)
-        );
+        ))
+        .unwrap();
+        assert_eq!(orbits.orbital_transfers("START", "END"), Ok(2));
     }
 
     #[test]
@@ -243,11 +523,9 @@ mod tests {
             B)C
             C)D
             D)END"
-        ));
-        assert_eq!(
-            get_num_orbital_transfers(&orbits, &"END", &"START"),
-            Some(2)
-        );
+        ))
+        .unwrap();
+        assert_eq!(orbits.orbital_transfers("END", "START"), Ok(2));
     }
 
     #[test]
@@ -266,8 +544,195 @@ mod tests {
             K)L
             K)YOU
             I)SAN"
-        ));
-        assert_eq!(get_num_orbital_transfers(&orbits, &"YOU", &"SAN"), Some(4));
-        assert_eq!(get_num_orbital_transfers(&orbits, &"SAN", &"YOU"), Some(4));
+        ))
+        .unwrap();
+        assert_eq!(orbits.orbital_transfers("YOU", "SAN"), Ok(4));
+        assert_eq!(orbits.orbital_transfers("SAN", "YOU"), Ok(4));
+    }
+
+    fn example() -> OrbitMap {
+        get_orbits(&indoc!(
+            "COM)B
+            B)C
+            C)D
+            D)E
+            E)F
+            B)G
+            G)H
+            D)I
+            E)J
+            J)K
+            K)L"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn depths_and_ancestors() {
+        let orbits = example();
+        let l = orbits.id("L").unwrap();
+
+        assert_eq!(part1(&orbits), 42);
+        assert_eq!(orbits.depth(l), 7);
+        assert_eq!(
+            orbits
+                .ancestors(l)
+                .into_iter()
+                .map(|body| orbits.name(body))
+                .collect::<Vec<&str>>(),
+            vec!["K", "J", "E", "D", "C", "B", "COM"]
+        );
+        assert_eq!(orbits.ancestors(orbits.root()), vec![]);
+    }
+
+    #[test]
+    fn lowest_common_ancestor() {
+        let orbits = example();
+        let id = |name| orbits.id(name).unwrap();
+
+        assert_eq!(orbits.lowest_common_ancestor(id("L"), id("I")), id("D"));
+        assert_eq!(orbits.lowest_common_ancestor(id("H"), id("F")), id("B"));
+        assert_eq!(orbits.lowest_common_ancestor(id("K"), id("E")), id("E"));
+        assert_eq!(orbits.distance(id("L"), id("I")), 5);
+        assert_eq!(orbits.distance(id("COM"), id("COM")), 0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            get_orbits("COM)A\nA-B").unwrap_err(),
+            OrbitError::MalformedLine {
+                line_number: 2,
+                line: "A-B".to_string()
+            }
+        );
+        assert_eq!(
+            get_orbits("COM)A\nCOM)B\nA)C\nB)C").unwrap_err(),
+            OrbitError::MultipleParents {
+                body: "C".to_string(),
+                parents: ["A".to_string(), "B".to_string()]
+            }
+        );
+        assert_eq!(
+            get_orbits("A)B\nB)C").unwrap_err(),
+            OrbitError::MissingCenterOfMass
+        );
+        assert_eq!(
+            get_orbits("COM)A\nB)C\nC)B").unwrap_err(),
+            OrbitError::Cycle {
+                body: "B".to_string()
+            }
+        );
+        assert_eq!(
+            get_orbits("COM)A\nA)COM").unwrap_err(),
+            OrbitError::Cycle {
+                body: "COM".to_string()
+            }
+        );
+        assert_eq!(
+            get_orbits("COM)A\nX)COM").unwrap_err(),
+            OrbitError::CenterOfMassOrbits {
+                parent: "X".to_string()
+            }
+        );
+        assert_eq!(
+            get_orbits("COM)A\nX)COM\nY)X\nX)Y").unwrap_err(),
+            OrbitError::Cycle {
+                body: "X".to_string()
+            }
+        );
+        assert_eq!(
+            get_orbits("COM)A\nB)C").unwrap_err(),
+            OrbitError::NotOrbitingCenterOfMass {
+                body: "B".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_bodies() {
+        assert_eq!(
+            example().orbital_transfers("YOU", "SAN"),
+            Err(OrbitError::UnknownBody {
+                body: "YOU".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn path_between_bodies() {
+        let orbits = example();
+        let id = |name| orbits.id(name).unwrap();
+
+        assert_eq!(
+            orbits
+                .path(id("L"), id("I"))
+                .into_iter()
+                .map(|body| orbits.name(body))
+                .collect::<Vec<&str>>(),
+            vec!["L", "K", "J", "E", "D", "I"]
+        );
+        assert_eq!(orbits.path(id("C"), id("C")), vec![id("C")]);
+    }
+
+    #[test]
+    fn summary() {
+        let summary = example().summary();
+        assert_eq!(
+            summary,
+            OrbitSummary {
+                bodies: 12,
+                direct_orbits: 11,
+                indirect_orbits: 31,
+                max_depth: 7,
+                leaf_count: 4,
+                deepest_chain: ["COM", "B", "C", "D", "E", "J", "K", "L"]
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+            }
+        );
+        assert_eq!(
+            summary.to_json(),
+            r#"{"bodies":12,"direct_orbits":11,"indirect_orbits":31,"total_orbits":42,"max_depth":7,"leaf_count":4,"deepest_chain":["COM","B","C","D","E","J","K","L"]}"#
+        );
+    }
+
+    #[test]
+    fn dot_export() {
+        let orbits = get_orbits(&indoc!(
+            "COM)A
+            A)B
+            COM)C"
+        ))
+        .unwrap();
+        let id = |name| orbits.id(name).unwrap();
+
+        assert_eq!(
+            orbits.to_dot(),
+            indoc!(
+                r#"digraph orbits {
+                  "COM" -> "A";
+                  "COM" -> "C";
+                  "A" -> "B";
+                }
+                "#
+            )
+        );
+        assert_eq!(
+            orbits.to_dot_with_path(id("B"), id("C")),
+            indoc!(
+                r#"digraph orbits {
+                  "B" [color=red, penwidth=2];
+                  "A" [color=red, penwidth=2];
+                  "COM" [color=red, penwidth=2];
+                  "C" [color=red, penwidth=2];
+                  "COM" -> "A" [color=red, penwidth=2];
+                  "COM" -> "C" [color=red, penwidth=2];
+                  "A" -> "B" [color=red, penwidth=2];
+                }
+                "#
+            )
+        );
     }
 }
@@ -110,6 +110,7 @@ fn get_max_result_with_feedback_loop(program_input: &[i64]) -> MaxResult {
                     a_computer.add_input(val);
                     a_result = extract_output(&(a_computer.run_program()));
                 }
+                ProgramOutput::NeedsInput => panic!("Amplifier E is waiting for input"),
                 ProgramOutput::Complete(values) => {
                     let final_output = values.last().unwrap();
                     if final_output > &res.thruster_output {
@@ -132,6 +133,7 @@ fn get_max_result_with_feedback_loop(program_input: &[i64]) -> MaxResult {
 fn extract_output(output: &ProgramOutput) -> i64 {
     match output {
         ProgramOutput::Yielded(val) => *val,
+        ProgramOutput::NeedsInput => panic!("Amplifier is waiting for input"),
         ProgramOutput::Complete(values) => *values.last().unwrap(),
     }
 }
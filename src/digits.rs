@@ -0,0 +1,39 @@
+/// A character in a digit stream that isn't a digit.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NotADigit {
+    pub index: usize,
+    pub found: char,
+}
+
+/// Reads a string of decimal digits, one per character. Trailing whitespace, such as
+/// the newline at the end of a puzzle input, is ignored.
+pub fn parse_digits(input: &str) -> Result<Vec<u8>, NotADigit> {
+    input
+        .trim_end()
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            c.to_digit(10)
+                .map(|digit| digit as u8)
+                .ok_or(NotADigit { index, found: c })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_streams() {
+        assert_eq!(parse_digits("0129\n"), Ok(vec![0, 1, 2, 9]));
+        assert_eq!(parse_digits(""), Ok(vec![]));
+        assert_eq!(
+            parse_digits("12 3"),
+            Err(NotADigit {
+                index: 2,
+                found: ' '
+            })
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use super::Point;
+use std::str::FromStr;
+
+/// Which way increasing y points when a grid is drawn. Puzzles that describe their
+/// coordinates as distance from the top of a screen are `Down`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum YAxis {
+    Up,
+    Down,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn all() -> [Direction; 4] {
+        [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ]
+    }
+
+    pub fn turn(self, turn: Turn) -> Self {
+        match turn {
+            Turn::Left => self.turn_left(),
+            Turn::Right => self.turn_right(),
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    /// The offset of a single step in this direction.
+    pub fn step(self, y_axis: YAxis) -> Point {
+        let up = match y_axis {
+            YAxis::Up => 1,
+            YAxis::Down => -1,
+        };
+        match self {
+            Direction::Up => Point { x: 0, y: up },
+            Direction::Down => Point { x: 0, y: -up },
+            Direction::Left => Point { x: -1, y: 0 },
+            Direction::Right => Point { x: 1, y: 0 },
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ();
+    fn from_str(letter: &str) -> Result<Self, Self::Err> {
+        match letter {
+            "U" => Ok(Direction::Up),
+            "D" => Ok(Direction::Down),
+            "L" => Ok(Direction::Left),
+            "R" => Ok(Direction::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Walks a grid one step at a time, turning relative to its current heading.
+#[derive(Clone, Debug)]
+pub struct Turtle {
+    pub position: Point,
+    pub heading: Direction,
+    y_axis: YAxis,
+}
+
+impl Turtle {
+    pub fn new(position: Point, heading: Direction, y_axis: YAxis) -> Self {
+        Turtle {
+            position,
+            heading,
+            y_axis,
+        }
+    }
+
+    pub fn turn(&mut self, turn: Turn) {
+        self.heading = self.heading.turn(turn);
+    }
+
+    /// The point one step ahead, without moving.
+    pub fn ahead(&self) -> Point {
+        self.position + self.heading.step(self.y_axis)
+    }
+
+    /// Moves one step forward and returns the new position.
+    pub fn step(&mut self) -> Point {
+        self.position = self.ahead();
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns() {
+        for direction in Direction::all().iter() {
+            assert_eq!(direction.turn_left().turn_right(), *direction);
+            assert_eq!(direction.turn_right().turn_right(), direction.reverse());
+            assert_eq!(direction.reverse().reverse(), *direction);
+        }
+        assert_eq!(Direction::Up.turn(Turn::Left), Direction::Left);
+        assert_eq!(Direction::Up.turn(Turn::Right), Direction::Right);
+    }
+
+    #[test]
+    fn steps_follow_y_axis() {
+        assert_eq!(Direction::Up.step(YAxis::Up), Point { x: 0, y: 1 });
+        assert_eq!(Direction::Up.step(YAxis::Down), Point { x: 0, y: -1 });
+        assert_eq!(Direction::Left.step(YAxis::Up), Point { x: -1, y: 0 });
+        assert_eq!(Direction::Left.step(YAxis::Down), Point { x: -1, y: 0 });
+    }
+
+    #[test]
+    fn turtle_walk() {
+        let mut turtle = Turtle::new(Point::origin(), Direction::Up, YAxis::Down);
+        assert_eq!(turtle.step(), Point { x: 0, y: -1 });
+        turtle.turn(Turn::Right);
+        assert_eq!(turtle.ahead(), Point { x: 1, y: -1 });
+        assert_eq!(turtle.step(), Point { x: 1, y: -1 });
+        assert_eq!(turtle.heading, Direction::Right);
+    }
+}
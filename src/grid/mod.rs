@@ -1,5 +1,13 @@
 use ordered_float::OrderedFloat;
 use std::cmp;
+use std::ops::Add;
+
+mod direction;
+mod point_n;
+mod search;
+pub use direction::{Direction, Turn, Turtle, YAxis};
+pub use point_n::{Point3, PointN};
+pub use search::{breadth_first, SearchTree};
 
 #[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
 pub struct Point {
@@ -11,9 +19,40 @@ impl Point {
     pub fn origin() -> Self {
         Point { x: 0, y: 0 }
     }
+
+    /// The smallest whole-number step from this point towards the other one. Points
+    /// that lie on the same ray from this point share a step.
+    pub fn step_towards(&self, other: &Point) -> Point {
+        let x_diff = other.x - self.x;
+        let y_diff = other.y - self.y;
+        let divisor = gcd(x_diff.abs(), y_diff.abs()).max(1);
+        Point {
+            x: x_diff / divisor,
+            y: y_diff / divisor,
+        }
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+pub fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Area {
     pub min_x: i32,
     pub min_y: i32,
@@ -45,6 +84,142 @@ impl Area {
 
         Area::new(min_x, min_y, max_x, max_y)
     }
+
+    /// An area covering just the one point.
+    pub fn around(point: &Point) -> Self {
+        Area::new(point.x, point.y, point.x, point.y)
+    }
+
+    /// Grows the area, if needed, so that it covers the point.
+    pub fn expand_to(&mut self, point: &Point) {
+        self.min_x = cmp::min(point.x, self.min_x);
+        self.min_y = cmp::min(point.y, self.min_y);
+        self.max_x = cmp::max(point.x, self.max_x);
+        self.max_y = cmp::max(point.y, self.max_y);
+    }
+}
+
+/// Renders one character per point, row by row from min_y to max_y.
+pub fn render_area<F>(area: &Area, to_char: F) -> String
+where
+    F: Fn(&Point) -> char,
+{
+    let mut result = String::new();
+    for y in area.min_y..=area.max_y {
+        for x in area.min_x..=area.max_x {
+            result.push(to_char(&Point { x, y }));
+        }
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Dense storage for a rectangle of values with its top left corner at the origin.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Grows the grid to the right and down, filling new cells with `fill`, until it
+    /// contains `point`. Points left of or above the origin can never fit.
+    pub fn expand_to(&mut self, point: &Point, fill: T) {
+        assert!(
+            point.x >= 0 && point.y >= 0,
+            "grids start at the origin, so {:?} can't be added",
+            point
+        );
+        let width = self.width.max(point.x as usize + 1);
+        let height = self.height.max(point.y as usize + 1);
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(if x < self.width && y < self.height {
+                    self.cells[y * self.width + x].clone()
+                } else {
+                    fill.clone()
+                });
+            }
+        }
+        *self = Grid {
+            width,
+            height,
+            cells,
+        };
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn area(&self) -> Area {
+        Area::new(0, 0, self.width as i32 - 1, self.height as i32 - 1)
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        point.x >= 0
+            && point.y >= 0
+            && (point.x as usize) < self.width
+            && (point.y as usize) < self.height
+    }
+
+    fn index_of(&self, point: &Point) -> Option<usize> {
+        if self.contains(point) {
+            Some(point.y as usize * self.width + point.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, point: &Point) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: &Point) -> Option<&mut T> {
+        self.index_of(point)
+            .map(move |index| &mut self.cells[index])
+    }
+
+    /// All points in the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.width * self.height).map(move |index| Point {
+            x: (index % width) as i32,
+            y: (index / width) as i32,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn render<F>(&self, to_char: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        render_area(&self.area(), |point| to_char(self.get(point).unwrap()))
+    }
 }
 
 #[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
@@ -75,6 +250,69 @@ impl Vector {
     }
 }
 
+#[cfg(test)]
+mod step_towards {
+    use super::*;
+
+    #[test]
+    fn reduces_to_smallest_step() {
+        let start = Point { x: 1, y: 1 };
+        assert_eq!(
+            start.step_towards(&Point { x: 5, y: 7 }),
+            Point { x: 2, y: 3 }
+        );
+        assert_eq!(
+            start.step_towards(&Point { x: -3, y: 1 }),
+            Point { x: -1, y: 0 }
+        );
+        assert_eq!(
+            start.step_towards(&Point { x: 1, y: 4 }),
+            Point { x: 0, y: 1 }
+        );
+        assert_eq!(start.step_towards(&start), Point { x: 0, y: 0 });
+    }
+}
+
+#[cfg(test)]
+mod dense_grid {
+    use super::*;
+
+    #[test]
+    fn get_and_set() {
+        let mut grid = Grid::new(3, 2, 0);
+        *grid.get_mut(&Point { x: 2, y: 1 }).unwrap() = 5;
+
+        assert_eq!(grid.get(&Point { x: 2, y: 1 }), Some(&5));
+        assert_eq!(grid.get(&Point { x: 1, y: 1 }), Some(&0));
+        assert_eq!(grid.get(&Point { x: 3, y: 1 }), None);
+        assert_eq!(grid.get(&Point { x: 0, y: -1 }), None);
+    }
+
+    #[test]
+    fn render() {
+        let mut grid = Grid::new(3, 2, false);
+        *grid.get_mut(&Point { x: 0, y: 0 }).unwrap() = true;
+        *grid.get_mut(&Point { x: 1, y: 1 }).unwrap() = true;
+
+        assert_eq!(
+            grid.render(|&set| if set { '#' } else { '.' }),
+            "#..\n.#.\n"
+        );
+    }
+
+    #[test]
+    fn expand_to() {
+        let mut grid = Grid::new(2, 1, 1);
+        grid.expand_to(&Point { x: 3, y: 1 }, 0);
+
+        assert_eq!((grid.width(), grid.height()), (4, 2));
+        assert_eq!(grid.render(|&value| (b'0' + value) as char), "1100\n0000\n");
+
+        grid.expand_to(&Point { x: 1, y: 1 }, 9);
+        assert_eq!((grid.width(), grid.height()), (4, 2));
+    }
+}
+
 #[cfg(test)]
 mod from_points {
     use super::*;
@@ -0,0 +1,185 @@
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+/// A point (or offset) with any number of integer axes.
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+pub struct PointN<const N: usize> {
+    coords: [i32; N],
+}
+
+pub type Point3 = PointN<3>;
+
+impl<const N: usize> PointN<N> {
+    pub fn zeros() -> Self {
+        PointN { coords: [0; N] }
+    }
+
+    pub fn num_axes(&self) -> usize {
+        N
+    }
+
+    /// The value along each axis, in axis order.
+    pub fn axes(&self) -> impl Iterator<Item = i32> {
+        IntoIterator::into_iter(self.coords)
+    }
+
+    /// Sum of the absolute values of each axis, i.e. the Manhattan distance from the origin.
+    pub fn manhattan_norm(&self) -> i32 {
+        self.axes().map(i32::abs).sum()
+    }
+
+    pub fn manhattan_distance(&self, other: &Self) -> i32 {
+        (*self - *other).manhattan_norm()
+    }
+
+    /// -1, 0 or 1 along each axis, depending on the sign of that axis.
+    pub fn signum(&self) -> Self {
+        self.map(i32::signum)
+    }
+
+    fn map<F>(&self, f: F) -> Self
+    where
+        F: Fn(i32) -> i32,
+    {
+        PointN {
+            coords: self.coords.map(f),
+        }
+    }
+
+    fn zip_with<F>(&self, other: &Self, f: F) -> Self
+    where
+        F: Fn(i32, i32) -> i32,
+    {
+        let mut coords = self.coords;
+        for (value, other_value) in coords.iter_mut().zip(other.coords.iter()) {
+            *value = f(*value, *other_value);
+        }
+        PointN { coords }
+    }
+}
+
+impl Point3 {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        PointN { coords: [x, y, z] }
+    }
+
+    pub fn x(&self) -> i32 {
+        self.coords[0]
+    }
+
+    pub fn y(&self) -> i32 {
+        self.coords[1]
+    }
+
+    pub fn z(&self) -> i32 {
+        self.coords[2]
+    }
+}
+
+impl<const N: usize> From<[i32; N]> for PointN<N> {
+    fn from(coords: [i32; N]) -> Self {
+        PointN { coords }
+    }
+}
+
+impl<const N: usize> Index<usize> for PointN<N> {
+    type Output = i32;
+
+    fn index(&self, axis: usize) -> &i32 {
+        &self.coords[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for PointN<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut i32 {
+        &mut self.coords[axis]
+    }
+}
+
+impl<const N: usize> Add for PointN<N> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.zip_with(&other, |a, b| a + b)
+    }
+}
+
+impl<const N: usize> Sub for PointN<N> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.zip_with(&other, |a, b| a - b)
+    }
+}
+
+impl<const N: usize> Neg for PointN<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.map(|value| -value)
+    }
+}
+
+impl<const N: usize> Mul<i32> for PointN<N> {
+    type Output = Self;
+
+    fn mul(self, scale: i32) -> Self {
+        self.map(|value| value * scale)
+    }
+}
+
+impl<const N: usize> AddAssign for PointN<N> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<const N: usize> SubAssign for PointN<N> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point3::new(1, -2, 3);
+        let b = Point3::new(4, 5, -6);
+
+        assert_eq!(a + b, Point3::new(5, 3, -3));
+        assert_eq!(a - b, Point3::new(-3, -7, 9));
+        assert_eq!(-a, Point3::new(-1, 2, -3));
+        assert_eq!(a * 2, Point3::new(2, -4, 6));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(c, b);
+    }
+
+    #[test]
+    fn manhattan() {
+        assert_eq!(Point3::new(2, 1, -3).manhattan_norm(), 6);
+        assert_eq!(
+            Point3::new(2, 1, -3).manhattan_distance(&Point3::new(-1, 1, 0)),
+            6
+        );
+    }
+
+    #[test]
+    fn signum() {
+        assert_eq!(Point3::new(-5, 0, 12).signum(), Point3::new(-1, 0, 1));
+    }
+
+    #[test]
+    fn axes() {
+        let mut point = PointN::from([4, 0, -1, 7]);
+        point[1] = 2;
+
+        assert_eq!(point.num_axes(), 4);
+        assert_eq!(point.axes().collect::<Vec<i32>>(), vec![4, 2, -1, 7]);
+        assert_eq!(point.axes().nth(3), Some(point[3]));
+    }
+}
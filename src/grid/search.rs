@@ -0,0 +1,98 @@
+use super::{Direction, Point, YAxis};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+/// Everywhere a breadth first search reached, with the number of steps to each point
+/// and the point it was first reached from.
+#[derive(Debug, Clone)]
+pub struct SearchTree {
+    start: Point,
+    reached: HashMap<Point, (usize, Option<Point>)>,
+}
+
+impl SearchTree {
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    pub fn distance(&self, point: &Point) -> Option<usize> {
+        self.reached.get(point).map(|&(distance, _)| distance)
+    }
+
+    /// Every point reached along with its distance, in no particular order.
+    pub fn distances(&self) -> impl Iterator<Item = (Point, usize)> + '_ {
+        self.reached
+            .iter()
+            .map(|(&point, &(distance, _))| (point, distance))
+    }
+
+    /// The points along a shortest path from the start, including both ends.
+    pub fn path_to(&self, point: &Point) -> Option<Vec<Point>> {
+        let mut path = vec![*point];
+        let mut current = self.reached.get(point)?;
+        while let (_, Some(previous)) = current {
+            path.push(*previous);
+            current = &self.reached[previous];
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+/// Searches outwards from `start` one orthogonal step at a time, onto every point that
+/// `passable` allows. The start is always included, whether it's passable or not.
+pub fn breadth_first<F>(start: Point, passable: F) -> SearchTree
+where
+    F: Fn(&Point) -> bool,
+{
+    let mut reached = HashMap::new();
+    reached.insert(start, (0, None));
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    while let Some(current) = queue.pop_front() {
+        let distance = reached[&current].0;
+        for direction in Direction::all().iter() {
+            let neighbour = current + direction.step(YAxis::Down);
+            if !passable(&neighbour) {
+                continue;
+            }
+            if let Entry::Vacant(entry) = reached.entry(neighbour) {
+                entry.insert((distance + 1, Some(current)));
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    SearchTree { start, reached }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_around_walls() {
+        // a 3x3 room with a wall sticking into the middle from the top
+        let passable = |point: &Point| {
+            (0..3).contains(&point.x) && (0..3).contains(&point.y) && !(point.x == 1 && point.y < 2)
+        };
+        let tree = breadth_first(Point::origin(), passable);
+
+        assert_eq!(tree.distance(&Point { x: 2, y: 0 }), Some(6));
+        assert_eq!(tree.distance(&Point { x: 1, y: 0 }), None);
+        assert_eq!(tree.distances().count(), 7);
+        assert_eq!(
+            tree.path_to(&Point { x: 2, y: 1 }),
+            Some(vec![
+                Point { x: 0, y: 0 },
+                Point { x: 0, y: 1 },
+                Point { x: 0, y: 2 },
+                Point { x: 1, y: 2 },
+                Point { x: 2, y: 2 },
+                Point { x: 2, y: 1 },
+            ])
+        );
+    }
+}
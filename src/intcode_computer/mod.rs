@@ -67,10 +67,13 @@ impl From<i64> for ParameterMode {
 
 pub enum ProgramOutput {
     Yielded(i64),
+    /// The program reached an input instruction with no input left to read. Running it
+    /// again after adding more input carries on from that instruction.
+    NeedsInput,
     Complete(Vec<i64>),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct IntcodeComputer {
     program: Vec<i64>,
     inputs: Vec<i64>,
@@ -113,6 +116,14 @@ impl IntcodeComputer {
         self.inputs.push(input);
     }
 
+    /// Forgets the outputs and inputs seen so far, for long running programs whose
+    /// callers handle each value as it comes.
+    pub fn clear_history(&mut self) {
+        self.outputs.clear();
+        self.inputs.drain(..self.next_input_index);
+        self.next_input_index = 0;
+    }
+
     fn get_value(&self, position: usize) -> i64 {
         match self.program.get(position) {
             Some(val) => *val,
@@ -121,11 +132,12 @@ impl IntcodeComputer {
     }
 
     fn set_value(&mut self, position: usize, value: i64, param_mode: &ParameterMode) {
-        let adjusted_position = position
+        // relative addresses can have negative offsets, so add them before converting
+        let adjusted_position = (position as i64
             + match param_mode {
                 ParameterMode::Relative => self.relative_base,
                 _ => 0,
-            } as usize;
+            }) as usize;
         if adjusted_position < self.program.len() {
             self.program[adjusted_position] = value;
         } else {
@@ -160,17 +172,12 @@ impl IntcodeComputer {
             match opcode {
                 Opcode::Halt => break,
                 Opcode::Input => {
+                    let input = match self.inputs.get(self.next_input_index) {
+                        Some(&input) => input,
+                        None => return ProgramOutput::NeedsInput,
+                    };
                     let output_index = self.get_value((self.instruction_pointer + 1) as usize);
-                    self.set_value(
-                        output_index as usize,
-                        *self
-                            .inputs
-                            .iter()
-                            .skip(self.next_input_index)
-                            .next()
-                            .unwrap(),
-                        &parameter_modes[0],
-                    );
+                    self.set_value(output_index as usize, input, &parameter_modes[0]);
                     self.next_input_index += 1;
                     self.instruction_pointer += 2;
                 }
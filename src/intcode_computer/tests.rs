@@ -199,11 +199,17 @@ mod relative_mode_and_extra_memory {
         let input = [109, 1, 204, -1, 99];
         let mut comp = IntcodeComputer::new(&input, &[]);
         match comp.run_program() {
-            ProgramOutput::Yielded(_) => panic!("Should not yield"),
+            ProgramOutput::Yielded(_) | ProgramOutput::NeedsInput => panic!("Should not yield"),
             ProgramOutput::Complete(output) => assert_eq!(output, [109]),
         }
     }
 
+    #[test]
+    fn relative_write_with_negative_offset() {
+        let input = [109, 10, 21101, 3, 4, -1, 204, -1, 99, 0];
+        assert_eq!(run_program(&input, &[]), [7]);
+    }
+
     #[test]
     fn quine() {
         let input = [
@@ -211,8 +217,45 @@ mod relative_mode_and_extra_memory {
         ];
         let mut comp = IntcodeComputer::new(&input, &[]);
         match comp.run_program() {
-            ProgramOutput::Yielded(_) => panic!("Should not yield"),
+            ProgramOutput::Yielded(_) | ProgramOutput::NeedsInput => panic!("Should not yield"),
             ProgramOutput::Complete(output) => assert_eq!(output, input),
         }
     }
 }
+
+#[cfg(test)]
+mod waiting_for_input {
+    use super::*;
+
+    #[test]
+    fn resumes_after_more_input() {
+        let echo_twice = [3, 9, 4, 9, 3, 9, 4, 9, 99, 0];
+        let mut comp = IntcodeComputer::yielding_computer(&echo_twice);
+
+        assert!(matches!(comp.run_program(), ProgramOutput::NeedsInput));
+        comp.add_input(5);
+        assert!(matches!(comp.run_program(), ProgramOutput::Yielded(5)));
+        assert!(matches!(comp.run_program(), ProgramOutput::NeedsInput));
+        assert!(matches!(comp.run_program(), ProgramOutput::NeedsInput));
+        comp.add_input(7);
+        assert!(matches!(comp.run_program(), ProgramOutput::Yielded(7)));
+        match comp.run_program() {
+            ProgramOutput::Complete(output) => assert_eq!(output, [5, 7]),
+            _ => panic!("Should have halted"),
+        }
+    }
+
+    #[test]
+    fn forgets_history() {
+        let echo_twice = [3, 9, 4, 9, 3, 9, 4, 9, 99, 0];
+        let mut comp = IntcodeComputer::new(&echo_twice, &[5]);
+
+        assert!(matches!(comp.run_program(), ProgramOutput::NeedsInput));
+        comp.clear_history();
+        comp.add_input(7);
+        match comp.run_program() {
+            ProgramOutput::Complete(output) => assert_eq!(output, [7]),
+            _ => panic!("Should have halted"),
+        }
+    }
+}
@@ -2,20 +2,28 @@ use aoc_runner_derive::aoc_lib;
 #[macro_use]
 extern crate lazy_static;
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+mod day16;
+pub mod day17;
+pub mod day18;
 mod day2;
-mod day3;
-mod day4;
+pub mod day3;
+pub mod day4;
 mod day5;
-mod day6;
+pub mod day6;
 mod day7;
 mod day8;
 mod day9;
 
-mod grid;
+mod digits;
+pub mod grid;
 mod intcode_computer;
+pub mod space_image_format;
 
 aoc_lib! { year = 2019 }
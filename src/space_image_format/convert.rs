@@ -0,0 +1,280 @@
+use super::{SifError, SifImage, BLACK, TRANSPARENT, WHITE};
+use std::convert::TryFrom;
+
+/// The RGBA colour used for each kind of pixel when exporting. Digits other than black
+/// and white are drawn as transparent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub black: [u8; 4],
+    pub white: [u8; 4],
+    pub transparent: [u8; 4],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            black: [0, 0, 0, 255],
+            white: [255, 255, 255, 255],
+            transparent: [0, 0, 0, 0],
+        }
+    }
+}
+
+impl Palette {
+    fn color(&self, digit: u8) -> [u8; 4] {
+        match digit {
+            BLACK => self.black,
+            WHITE => self.white,
+            _ => self.transparent,
+        }
+    }
+}
+
+/// A black and white picture, stored row by row with `true` for white.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    /// Reads a plain (P1) or raw (P4) portable bitmap. In PBM a 1 is black.
+    pub fn parse_pbm(bytes: &[u8]) -> Result<Self, SifError> {
+        let mut header = PbmHeader { bytes, position: 0 };
+        let magic = header.token()?;
+        let width = header.number()?;
+        let height = header.number()?;
+        // the header is untrusted, so its sizes mustn't be allowed to overflow
+        let size = width
+            .checked_mul(height)
+            .ok_or_else(|| invalid_bitmap("the header's size is too large"))?;
+
+        let pixels = match magic.as_str() {
+            "P1" => (0..size)
+                .map(|_| header.bit())
+                .collect::<Result<Vec<bool>, SifError>>()?,
+            "P4" => {
+                // exactly one whitespace byte separates the header from the raster
+                let raster = bytes.get(header.position + 1..).unwrap_or(&[]);
+                let row_bytes = width.div_ceil(8);
+                let raster_size = row_bytes
+                    .checked_mul(height)
+                    .ok_or_else(|| invalid_bitmap("the header's size is too large"))?;
+                if raster.len() < raster_size {
+                    return Err(invalid_bitmap("raster is shorter than the header says"));
+                }
+                (0..size)
+                    .map(|index| {
+                        let (y, x) = (index / width, index % width);
+                        let byte = raster[y * row_bytes + x / 8];
+                        byte & (0x80 >> (x % 8)) == 0
+                    })
+                    .collect()
+            }
+            _ => return Err(invalid_bitmap("only P1 and P4 bitmaps are supported")),
+        };
+
+        Ok(Bitmap {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Reads a PNG, treating any pixel at least half as bright as white as white.
+    pub fn decode_png(bytes: &[u8]) -> Result<Self, SifError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder
+            .read_info()
+            .map_err(|error| invalid_bitmap(&error.to_string()))?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|error| invalid_bitmap(&error.to_string()))?;
+
+        let channels = info.color_type.samples();
+        let pixels = buffer[..info.buffer_size()]
+            .chunks(channels)
+            .map(|pixel| {
+                let color_channels = if channels >= 3 { 3 } else { 1 };
+                let brightness = pixel[..color_channels]
+                    .iter()
+                    .map(|&value| u32::from(value))
+                    .sum::<u32>()
+                    / color_channels as u32;
+                brightness >= 128
+            })
+            .collect();
+
+        Ok(Bitmap {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+}
+
+fn invalid_bitmap(reason: &str) -> SifError {
+    SifError::InvalidBitmap(reason.to_string())
+}
+
+fn encoding_error(error: png::EncodingError) -> SifError {
+    SifError::Encoding(error.to_string())
+}
+
+struct PbmHeader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PbmHeader<'a> {
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            if byte == b'#' {
+                while self.bytes.get(self.position).is_some_and(|&b| b != b'\n') {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<String, SifError> {
+        self.skip_whitespace_and_comments();
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|b| !b.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(invalid_bitmap("header ended early"));
+        }
+        Ok(String::from_utf8_lossy(&self.bytes[start..self.position]).into_owned())
+    }
+
+    fn number(&mut self) -> Result<usize, SifError> {
+        self.token()?
+            .parse::<usize>()
+            .map_err(|_| invalid_bitmap("expected a number in the header"))
+    }
+
+    /// Plain bitmaps allow their pixels to run together without whitespace.
+    fn bit(&mut self) -> Result<bool, SifError> {
+        self.skip_whitespace_and_comments();
+        let bit = match self.bytes.get(self.position) {
+            Some(b'0') => true,
+            Some(b'1') => false,
+            _ => return Err(invalid_bitmap("expected a 0 or 1 pixel")),
+        };
+        self.position += 1;
+        Ok(bit)
+    }
+}
+
+impl SifImage {
+    /// Spreads a bitmap across layers: each pixel is drawn on one layer (cycling through
+    /// them), is transparent on the layers in front of it and inverted on the layers
+    /// behind it, so only a correct flatten recovers the picture.
+    pub fn from_bitmap(bitmap: &Bitmap, num_layers: usize) -> Result<Self, SifError> {
+        if num_layers == 0 {
+            return Err(SifError::ZeroSize);
+        }
+        let layer_size = bitmap.width * bitmap.height;
+        if bitmap.pixels.len() != layer_size {
+            return Err(invalid_bitmap(&format!(
+                "{} pixels don't fill a {}x{} bitmap",
+                bitmap.pixels.len(),
+                bitmap.width,
+                bitmap.height
+            )));
+        }
+        let mut digits = vec![TRANSPARENT; layer_size * num_layers];
+
+        for (index, &is_white) in bitmap.pixels.iter().enumerate() {
+            let (shown, hidden) = if is_white {
+                (WHITE, BLACK)
+            } else {
+                (BLACK, WHITE)
+            };
+            let visible_layer = index % num_layers;
+            digits[visible_layer * layer_size + index] = shown;
+            for layer in visible_layer + 1..num_layers {
+                digits[layer * layer_size + index] = hidden;
+            }
+        }
+
+        SifImage::from_digits(&digits, bitmap.width, bitmap.height)
+    }
+
+    /// The width and height of the image once each pixel is blown up to a `scale` by
+    /// `scale` square, as long as they fit in the 32 bits image formats allow.
+    fn scaled_size(&self, scale: usize) -> Result<(u32, u32), SifError> {
+        if scale == 0 {
+            return Err(SifError::ZeroScale);
+        }
+        let scaled = |length: usize| {
+            length
+                .checked_mul(scale)
+                .and_then(|length| u32::try_from(length).ok())
+                .ok_or(SifError::TooLargeToExport { scale })
+        };
+
+        Ok((scaled(self.width)?, scaled(self.height)?))
+    }
+
+    /// The flattened image, with each pixel blown up to a `scale` by `scale` square.
+    /// The scale has to have been checked with `scaled_size` first.
+    fn scaled_rgba(&self, scale: usize, palette: &Palette) -> Vec<[u8; 4]> {
+        let pixels = self.flatten();
+        let mut rgba = Vec::with_capacity(pixels.len() * scale * scale);
+        for row in pixels.chunks(self.width) {
+            for _ in 0..scale {
+                for &digit in row {
+                    for _ in 0..scale {
+                        rgba.push(palette.color(digit));
+                    }
+                }
+            }
+        }
+
+        rgba
+    }
+
+    /// A binary (P6) portable pixmap. PPM has no transparency, so alpha is ignored.
+    pub fn to_ppm(&self, scale: usize, palette: &Palette) -> Result<Vec<u8>, SifError> {
+        let (width, height) = self.scaled_size(scale)?;
+        let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        for pixel in self.scaled_rgba(scale, palette) {
+            ppm.extend_from_slice(&pixel[..3]);
+        }
+
+        Ok(ppm)
+    }
+
+    pub fn to_png(&self, scale: usize, palette: &Palette) -> Result<Vec<u8>, SifError> {
+        let (width, height) = self.scaled_size(scale)?;
+        let mut png_bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png_bytes, width, height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().map_err(encoding_error)?;
+            let data: Vec<u8> = self
+                .scaled_rgba(scale, palette)
+                .into_iter()
+                .flatten()
+                .collect();
+            writer.write_image_data(&data).map_err(encoding_error)?;
+        }
+
+        Ok(png_bytes)
+    }
+}
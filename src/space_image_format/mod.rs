@@ -0,0 +1,157 @@
+use crate::digits::{parse_digits, NotADigit};
+use crate::grid::{render_area, Area, Point};
+use std::error::Error;
+use std::fmt;
+
+mod convert;
+pub use convert::{Bitmap, Palette};
+
+pub const BLACK: u8 = 0;
+pub const WHITE: u8 = 1;
+pub const TRANSPARENT: u8 = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SifError {
+    ZeroSize,
+    NotADigit { index: usize, found: char },
+    IncompleteLayer { length: usize, layer_size: usize },
+    InvalidBitmap(String),
+    ZeroScale,
+    TooLargeToExport { scale: usize },
+    Encoding(String),
+}
+
+impl fmt::Display for SifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SifError::ZeroSize => write!(f, "width and height must both be non-zero"),
+            SifError::NotADigit { index, found } => {
+                write!(
+                    f,
+                    "expected a digit at index {} but found {:?}",
+                    index, found
+                )
+            }
+            SifError::IncompleteLayer { length, layer_size } => write!(
+                f,
+                "{} digits can't be split into layers of {}",
+                length, layer_size
+            ),
+            SifError::InvalidBitmap(reason) => write!(f, "invalid bitmap: {}", reason),
+            SifError::ZeroScale => write!(f, "images must be exported at a scale of at least 1"),
+            SifError::TooLargeToExport { scale } => {
+                write!(
+                    f,
+                    "the image is too large to export at a scale of {}",
+                    scale
+                )
+            }
+            SifError::Encoding(reason) => write!(f, "couldn't encode the image: {}", reason),
+        }
+    }
+}
+
+impl Error for SifError {}
+
+impl From<NotADigit> for SifError {
+    fn from(NotADigit { index, found }: NotADigit) -> Self {
+        SifError::NotADigit { index, found }
+    }
+}
+
+/// A Space Image Format image: a stack of layers, first layer in front, each holding
+/// one digit per pixel in row order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SifImage {
+    width: usize,
+    height: usize,
+    layers: Vec<Vec<u8>>,
+}
+
+impl SifImage {
+    /// Trailing whitespace, such as the newline at the end of a puzzle input, is ignored.
+    pub fn parse(input: &str, width: usize, height: usize) -> Result<Self, SifError> {
+        SifImage::from_digits(&parse_digits(input)?, width, height)
+    }
+
+    pub fn from_digits(digits: &[u8], width: usize, height: usize) -> Result<Self, SifError> {
+        let layer_size = width * height;
+        if layer_size == 0 {
+            return Err(SifError::ZeroSize);
+        }
+        if !digits.len().is_multiple_of(layer_size) {
+            return Err(SifError::IncompleteLayer {
+                length: digits.len(),
+                layer_size,
+            });
+        }
+
+        Ok(SifImage {
+            width,
+            height,
+            layers: digits.chunks(layer_size).map(Vec::from).collect(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn layers(&self) -> &[Vec<u8>] {
+        &self.layers
+    }
+
+    /// The image as it appears from the front: each pixel takes the first digit that
+    /// isn't transparent. Pixels that are transparent all the way through stay that way.
+    pub fn flatten(&self) -> Vec<u8> {
+        (0..self.width * self.height)
+            .map(|index| {
+                self.layers
+                    .iter()
+                    .map(|layer| layer[index])
+                    .find(|&digit| digit != TRANSPARENT)
+                    .unwrap_or(TRANSPARENT)
+            })
+            .collect()
+    }
+
+    /// Within the layer with the fewest 0 digits, the number of 1 digits multiplied by
+    /// the number of 2 digits.
+    pub fn checksum(&self) -> usize {
+        let count = |layer: &Vec<u8>, digit: u8| layer.iter().filter(|&&d| d == digit).count();
+
+        self.layers
+            .iter()
+            .min_by_key(|layer| count(layer, 0))
+            .map_or(0, |layer| count(layer, 1) * count(layer, 2))
+    }
+
+    /// The digit stream this image was parsed from.
+    pub fn encode(&self) -> String {
+        self.layers
+            .iter()
+            .flatten()
+            .map(|&digit| (b'0' + digit) as char)
+            .collect()
+    }
+
+    /// Draws the flattened image with `#` for white and a space for anything else.
+    pub fn render(&self) -> String {
+        let pixels = self.flatten();
+        let area = Area::new(0, 0, self.width as i32 - 1, self.height as i32 - 1);
+
+        render_area(&area, |&Point { x, y }| {
+            match pixels[y as usize * self.width + x as usize] {
+                WHITE => '#',
+                _ => ' ',
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
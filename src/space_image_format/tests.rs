@@ -0,0 +1,190 @@
+use super::{Bitmap, Palette, SifError, SifImage, TRANSPARENT};
+
+#[cfg(test)]
+mod parsing {
+    use super::*;
+
+    #[test]
+    fn splits_into_layers() {
+        let image = SifImage::parse("123456789012\n", 3, 2).unwrap();
+        assert_eq!(
+            image.layers(),
+            &[vec![1, 2, 3, 4, 5, 6], vec![7, 8, 9, 0, 1, 2]]
+        );
+    }
+
+    #[test]
+    fn rejects_partial_layers() {
+        assert_eq!(
+            SifImage::parse("1234567", 3, 2),
+            Err(SifError::IncompleteLayer {
+                length: 7,
+                layer_size: 6
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_non_digits() {
+        assert_eq!(
+            SifImage::parse("12x4", 2, 2),
+            Err(SifError::NotADigit {
+                index: 2,
+                found: 'x'
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_empty_layers() {
+        assert_eq!(SifImage::parse("", 0, 6), Err(SifError::ZeroSize));
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        let input = "0222112222120000";
+        assert_eq!(SifImage::parse(input, 2, 2).unwrap().encode(), input);
+    }
+}
+
+#[cfg(test)]
+mod decoding {
+    use super::*;
+
+    #[test]
+    fn checksum() {
+        // the first layer has the fewest zeros: 3 zeros, 5 ones and 3 twos
+        let image = SifImage::parse("012302101121000000111222", 3, 4).unwrap();
+        assert_eq!(image.checksum(), 15);
+    }
+
+    #[test]
+    fn flatten() {
+        let image = SifImage::parse("02221122221200002222", 2, 2).unwrap();
+        assert_eq!(image.flatten(), vec![0, 1, 1, 0]);
+
+        let see_through = SifImage::parse("22122222", 2, 2).unwrap();
+        assert_eq!(
+            see_through.flatten(),
+            vec![TRANSPARENT, TRANSPARENT, 1, TRANSPARENT]
+        );
+    }
+
+    #[test]
+    fn render() {
+        let image = SifImage::parse("0222112222120000", 2, 2).unwrap();
+        assert_eq!(image.render(), " #\n# \n");
+    }
+}
+
+#[cfg(test)]
+mod conversion {
+    use super::*;
+
+    fn checkerboard() -> Bitmap {
+        Bitmap {
+            width: 3,
+            height: 2,
+            pixels: vec![true, false, true, false, true, false],
+        }
+    }
+
+    #[test]
+    fn ppm_export() {
+        let image = SifImage::parse("01212220", 2, 2).unwrap();
+        let palette = Palette {
+            white: [255, 0, 0, 255],
+            ..Palette::default()
+        };
+
+        let ppm = image.to_ppm(1, &palette).unwrap();
+        assert_eq!(&ppm[..11], b"P6\n2 2\n255\n");
+        assert_eq!(&ppm[11..], &[0, 0, 0, 255, 0, 0, 0, 0, 0, 255, 0, 0]);
+
+        let scaled = image.to_ppm(2, &palette).unwrap();
+        assert_eq!(&scaled[..11], b"P6\n4 4\n255\n");
+        assert_eq!(&scaled[11..17], &[0, 0, 0, 0, 0, 0]);
+        assert_eq!(scaled.len() - 11, 4 * 4 * 3);
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let image = SifImage::from_bitmap(&checkerboard(), 1).unwrap();
+        let png = image.to_png(1, &Palette::default()).unwrap();
+        assert_eq!(Bitmap::decode_png(&png), Ok(checkerboard()));
+
+        let scaled = Bitmap::decode_png(&image.to_png(3, &Palette::default()).unwrap()).unwrap();
+        assert_eq!((scaled.width, scaled.height), (9, 6));
+        assert!(scaled.pixels[..3].iter().all(|&white| white));
+        assert!(!scaled.pixels[3]);
+    }
+
+    #[test]
+    fn bad_scales() {
+        let image = SifImage::from_bitmap(&checkerboard(), 1).unwrap();
+        assert_eq!(
+            image.to_ppm(0, &Palette::default()),
+            Err(SifError::ZeroScale)
+        );
+        assert_eq!(
+            image.to_png(0, &Palette::default()),
+            Err(SifError::ZeroScale)
+        );
+        let huge = usize::MAX / 2;
+        assert_eq!(
+            image.to_png(huge, &Palette::default()),
+            Err(SifError::TooLargeToExport { scale: huge })
+        );
+        let too_wide = u32::MAX as usize / 2;
+        assert_eq!(
+            image.to_ppm(too_wide, &Palette::default()),
+            Err(SifError::TooLargeToExport { scale: too_wide })
+        );
+    }
+
+    #[test]
+    fn pbm_import() {
+        let plain = Bitmap::parse_pbm(b"P1\n# a comment\n3 2\n0 1 0\n101\n").unwrap();
+        assert_eq!(plain, checkerboard());
+
+        let raw = Bitmap::parse_pbm(b"P4\n3 2\n\x40\xa0").unwrap();
+        assert_eq!(raw, checkerboard());
+
+        assert!(matches!(
+            Bitmap::parse_pbm(b"P1\n3 2\n0 1"),
+            Err(SifError::InvalidBitmap(_))
+        ));
+        assert!(matches!(
+            Bitmap::parse_pbm(b"P3\n1 1\n255\n0 0 0"),
+            Err(SifError::InvalidBitmap(_))
+        ));
+        let overflowing = format!("P4\n{} {}\n", usize::MAX, 2);
+        assert!(matches!(
+            Bitmap::parse_pbm(overflowing.as_bytes()),
+            Err(SifError::InvalidBitmap(_))
+        ));
+    }
+
+    #[test]
+    fn bitmap_to_layers() {
+        let image = SifImage::from_bitmap(&checkerboard(), 3).unwrap();
+        assert_eq!(image.layers().len(), 3);
+        assert_eq!(image.flatten(), vec![1, 0, 1, 0, 1, 0]);
+        assert_eq!(
+            SifImage::parse(&image.encode(), 3, 2).unwrap().flatten(),
+            image.flatten()
+        );
+
+        assert_eq!(
+            SifImage::from_bitmap(&checkerboard(), 0),
+            Err(SifError::ZeroSize)
+        );
+
+        let mut too_long = checkerboard();
+        too_long.pixels.push(true);
+        assert!(matches!(
+            SifImage::from_bitmap(&too_long, 2),
+            Err(SifError::InvalidBitmap(_))
+        ));
+    }
+}